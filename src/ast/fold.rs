@@ -0,0 +1,120 @@
+use crate::ast::{BlockStatement, Expression, LetStatement, Program, ReturnStatement, Statement};
+
+/// Rebuilds a syntax tree by value, so that a pass can replace a node with a different kind of
+/// node. Each method's default implementation folds the node's children through the matching free
+/// function and reassembles the node around them.
+pub trait Folder: Sized {
+    fn fold_program(&mut self, program: Program) -> Program {
+        fold_program(self, program)
+    }
+
+    fn fold_statement(&mut self, statement: Statement) -> Statement {
+        fold_statement(self, statement)
+    }
+
+    fn fold_let_statement(&mut self, let_statement: LetStatement) -> LetStatement {
+        fold_let_statement(self, let_statement)
+    }
+
+    fn fold_return_statement(&mut self, return_statement: ReturnStatement) -> ReturnStatement {
+        fold_return_statement(self, return_statement)
+    }
+
+    fn fold_block_statement(&mut self, block: BlockStatement) -> BlockStatement {
+        fold_block_statement(self, block)
+    }
+
+    fn fold_expression(&mut self, expression: Expression) -> Expression {
+        fold_expression(self, expression)
+    }
+}
+
+pub fn fold_program<F: Folder>(folder: &mut F, program: Program) -> Program {
+    Program {
+        statements: program.statements.into_iter().map(|statement| folder.fold_statement(statement)).collect(),
+    }
+}
+
+pub fn fold_statement<F: Folder>(folder: &mut F, statement: Statement) -> Statement {
+    match statement {
+        Statement::Let(let_statement) => Statement::Let(folder.fold_let_statement(let_statement)),
+        Statement::Return(return_statement) => Statement::Return(folder.fold_return_statement(return_statement)),
+        Statement::Expression(expression) => Statement::Expression(folder.fold_expression(expression)),
+        Statement::Error(span) => Statement::Error(span),
+    }
+}
+
+pub fn fold_let_statement<F: Folder>(folder: &mut F, let_statement: LetStatement) -> LetStatement {
+    LetStatement {
+        value: folder.fold_expression(let_statement.value),
+        ..let_statement
+    }
+}
+
+pub fn fold_return_statement<F: Folder>(folder: &mut F, return_statement: ReturnStatement) -> ReturnStatement {
+    ReturnStatement {
+        value: folder.fold_expression(return_statement.value),
+        ..return_statement
+    }
+}
+
+pub fn fold_block_statement<F: Folder>(folder: &mut F, block: BlockStatement) -> BlockStatement {
+    BlockStatement {
+        statements: block.statements.into_iter().map(|statement| folder.fold_statement(statement)).collect(),
+        span: block.span,
+    }
+}
+
+pub fn fold_expression<F: Folder>(folder: &mut F, expression: Expression) -> Expression {
+    match expression {
+        Expression::Identifier { .. }
+        | Expression::Boolean { .. }
+        | Expression::IntegerLiteral { .. }
+        | Expression::StringLiteral { .. }
+        | Expression::Error { .. } => expression,
+        Expression::PrefixExpression { operator, right, span } => Expression::PrefixExpression {
+            operator,
+            right: Box::new(folder.fold_expression(*right)),
+            span,
+        },
+        Expression::InfixExpression { operator, left, right, span } => Expression::InfixExpression {
+            operator,
+            left: Box::new(folder.fold_expression(*left)),
+            right: Box::new(folder.fold_expression(*right)),
+            span,
+        },
+        Expression::IfExpression { token, condition, consequence, alternative, span } => Expression::IfExpression {
+            token,
+            condition: Box::new(folder.fold_expression(*condition)),
+            consequence: consequence.map(|block| folder.fold_block_statement(block)),
+            alternative: alternative.map(|block| folder.fold_block_statement(block)),
+            span,
+        },
+        Expression::Function { token, parameters, body, span } => Expression::Function {
+            token,
+            parameters: parameters.into_iter().map(|parameter| folder.fold_expression(parameter)).collect(),
+            body: folder.fold_block_statement(body),
+            span,
+        },
+        Expression::Call { function, arguments, span } => Expression::Call {
+            function: Box::new(folder.fold_expression(*function)),
+            arguments: arguments.into_iter().map(|argument| folder.fold_expression(argument)).collect(),
+            span,
+        },
+        Expression::ArrayLiteral { elements, span } => Expression::ArrayLiteral {
+            elements: elements.into_iter().map(|element| folder.fold_expression(element)).collect(),
+            span,
+        },
+        Expression::Index { left, index, span } => Expression::Index {
+            left: Box::new(folder.fold_expression(*left)),
+            index: Box::new(folder.fold_expression(*index)),
+            span,
+        },
+        Expression::HashLiteral { pairs, span } => Expression::HashLiteral {
+            pairs: pairs.into_iter()
+                .map(|(key, value)| (folder.fold_expression(key), folder.fold_expression(value)))
+                .collect(),
+            span,
+        },
+    }
+}
@@ -1,52 +1,128 @@
+pub mod fold;
+pub mod printer;
+pub mod scope;
+pub mod visit;
+pub mod visit_mut;
+
 use std::fmt::{Display, Formatter};
+use crate::ast::printer::Print;
 use crate::token;
-use crate::token::Token;
+use crate::token::{Span, Token};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Statement {
     Let(LetStatement),
-    Return(Expression),
+    Return(ReturnStatement),
     Expression(Expression),
+    /// Stands in for a statement that failed to parse.
+    Error(Span),
+}
+
+impl Statement {
+    pub fn span(&self) -> Span {
+        match self {
+            Statement::Let(let_statement) => let_statement.span,
+            Statement::Return(return_statement) => return_statement.span,
+            Statement::Expression(expression) => expression.span(),
+            Statement::Error(span) => *span,
+        }
+    }
 }
 
 impl Display for Statement {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.print(&mut printer::new(f, false))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expression {
+    Identifier { token: token::Token, span: Span },
+    Boolean { token: token::Token, value: bool, span: Span },
+    IntegerLiteral { token: token::Token, value: i64, span: Span },
+    StringLiteral { token: token::Token, value: String, span: Span },
+    PrefixExpression { operator: String, right: Box<Expression>, span: Span },
+    InfixExpression { operator: String, left: Box<Expression>, right: Box<Expression>, span: Span },
+    IfExpression { token: token::Token, condition: Box<Expression>, consequence: Option<BlockStatement>, alternative: Option<BlockStatement>, span: Span },
+    Function { token: Token, parameters: Vec<Expression>, body: BlockStatement, span: Span },
+    Call { function: Box<Expression>, arguments: Vec<Expression>, span: Span },
+    ArrayLiteral { elements: Vec<Expression>, span: Span },
+    Index { left: Box<Expression>, index: Box<Expression>, span: Span },
+    HashLiteral { pairs: Vec<(Expression, Expression)>, span: Span },
+    /// Stands in for an expression that failed to parse.
+    Error { span: Span },
+}
+
+impl Expression {
+    pub fn span(&self) -> Span {
         match self {
-            Statement::Let(let_statement) => let_statement.fmt(f),
-            Statement::Return(expression) => write!(f, "return {:?};", expression),
-            Statement::Expression(expression) => write!(f, "{:?}", expression)
+            Expression::Identifier { span, .. }
+            | Expression::Boolean { span, .. }
+            | Expression::IntegerLiteral { span, .. }
+            | Expression::StringLiteral { span, .. }
+            | Expression::PrefixExpression { span, .. }
+            | Expression::InfixExpression { span, .. }
+            | Expression::IfExpression { span, .. }
+            | Expression::Function { span, .. }
+            | Expression::Call { span, .. }
+            | Expression::ArrayLiteral { span, .. }
+            | Expression::Index { span, .. }
+            | Expression::HashLiteral { span, .. }
+            | Expression::Error { span } => *span,
         }
     }
 }
 
-#[derive(Debug)]
-pub enum Expression {
-    Identifier(token::Token),
-    Boolean { token: token::Token, value: bool },
-    IntegerLiteral { token: token::Token, value: i64 },
-    PrefixExpression { operator: String, right: Box<Expression> },
-    InfixExpression { operator: String, left: Box<Expression>, right: Box<Expression> },
-    IfExpression { token: token::Token, condition: Box<Expression>, consequence: Option<BlockStatement>, alternative: Option<BlockStatement> },
-    Function { token: Token, parameters: Vec<Expression>, body: BlockStatement },
+impl Display for Expression {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.print(&mut printer::new(f, false))
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct BlockStatement {
     pub statements: Vec<Statement>,
+    pub span: Span,
 }
 
+impl Display for BlockStatement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.print(&mut printer::new(f, false))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Program {
     pub statements: Vec<Statement>,
 }
 
-#[derive(Debug)]
+impl Display for Program {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.print(&mut printer::new(f, false))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct LetStatement {
     pub identifier_name: String,
     pub value: Expression,
+    pub span: Span,
 }
 
 impl Display for LetStatement {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "let {} = {:?};", self.identifier_name, self.value)
+        self.print(&mut printer::new(f, false))
     }
-}
\ No newline at end of file
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReturnStatement {
+    pub value: Expression,
+    pub span: Span,
+}
+
+impl Display for ReturnStatement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.print(&mut printer::new(f, false))
+    }
+}
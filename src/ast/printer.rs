@@ -0,0 +1,462 @@
+use std::collections::VecDeque;
+use std::fmt::{Display, Formatter, Result, Write};
+use crate::ast::{BlockStatement, Expression, LetStatement, Program, ReturnStatement, Statement};
+use crate::parser::operator::{self, Associativity};
+use crate::parser::Precedence;
+use crate::token::{Span, Trivia, TriviaKind};
+
+const INDENT: &str = "    ";
+
+/// Writes syntax trees out as Monkey source that parses back to the same tree. Statements end
+/// in `;` and blocks are spread over indented lines. Parentheses are only added where precedence
+/// requires them, unless `parenthesize` is set, in which case every prefix, infix and index
+/// expression is wrapped so that the grouping the parser chose is visible. The placeholders left
+/// by error recovery print as `<error>`, which does not parse.
+pub struct Printer<W: Write> {
+    output: W,
+    indent: usize,
+    column: usize,
+    parenthesize: bool,
+    line_width: Option<usize>,
+    source: String,
+    comments: VecDeque<Trivia>,
+}
+
+pub fn new<W: Write>(output: W, parenthesize: bool) -> Printer<W> {
+    Printer {
+        output,
+        indent: 0,
+        column: 0,
+        parenthesize,
+        line_width: None,
+        source: String::new(),
+        comments: VecDeque::new(),
+    }
+}
+
+/// A syntax tree node that can be printed as Monkey source.
+pub trait Print {
+    fn print<W: Write>(&self, printer: &mut Printer<W>) -> Result;
+
+    /// Displays the node with every prefix, infix and index expression parenthesized.
+    fn parenthesized(&self) -> Parenthesized<'_, Self> where Self: Sized {
+        Parenthesized(self)
+    }
+}
+
+pub struct Parenthesized<'a, T: Print>(&'a T);
+
+impl<T: Print> Display for Parenthesized<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        self.0.print(&mut new(f, true))
+    }
+}
+
+impl Print for Program {
+    fn print<W: Write>(&self, printer: &mut Printer<W>) -> Result {
+        printer.print_program(self)
+    }
+}
+
+impl Print for Statement {
+    fn print<W: Write>(&self, printer: &mut Printer<W>) -> Result {
+        printer.print_statement(self)
+    }
+}
+
+impl Print for LetStatement {
+    fn print<W: Write>(&self, printer: &mut Printer<W>) -> Result {
+        printer.print_let_statement(self)
+    }
+}
+
+impl Print for ReturnStatement {
+    fn print<W: Write>(&self, printer: &mut Printer<W>) -> Result {
+        printer.print_return_statement(self)
+    }
+}
+
+impl Print for BlockStatement {
+    fn print<W: Write>(&self, printer: &mut Printer<W>) -> Result {
+        printer.print_block_statement(self)
+    }
+}
+
+impl Print for Expression {
+    fn print<W: Write>(&self, printer: &mut Printer<W>) -> Result {
+        printer.print_expression(self)
+    }
+}
+
+impl Print for (Expression, Expression) {
+    fn print<W: Write>(&self, printer: &mut Printer<W>) -> Result {
+        printer.print_expression(&self.0)?;
+        printer.write_str(": ")?;
+        printer.print_expression(&self.1)
+    }
+}
+
+/// An element of a delimited list, which comments can be written around.
+trait ListItem: Print {
+    fn span(&self) -> Span;
+}
+
+impl ListItem for Expression {
+    fn span(&self) -> Span {
+        Expression::span(self)
+    }
+}
+
+impl ListItem for (Expression, Expression) {
+    fn span(&self) -> Span {
+        self.0.span().to(self.1.span())
+    }
+}
+
+impl<W: Write> Write for Printer<W> {
+    fn write_str(&mut self, text: &str) -> Result {
+        match text.rfind('\n') {
+            Some(index) => self.column = text[index + 1..].chars().count(),
+            None => self.column += text.chars().count(),
+        }
+        self.output.write_str(text)
+    }
+}
+
+impl<W: Write> Printer<W> {
+    /// Wraps delimited lists, such as call arguments, one element per line when printing them on
+    /// a single line would run past `line_width` columns.
+    pub fn with_line_width(mut self, line_width: usize) -> Self {
+        self.line_width = Some(line_width);
+        self
+    }
+
+    /// Prints `comments`, taken from `source`, between the statements or list items they were
+    /// written between, or in front of the expression they were written before. A comment that
+    /// follows code on its line stays at the end of that line, and a single blank line is kept
+    /// wherever the source separated statements with one or more.
+    ///
+    /// Comments are only placed at those points, so one written after the last operand of an
+    /// expression moves to the next of them: the end of the statement's line, or the start of the
+    /// block when it is in an `if` condition or between a `)` and the `{` that follows.
+    pub fn with_comments(mut self, source: &str, comments: Vec<Trivia>) -> Self {
+        self.source = source.to_string();
+        self.comments = comments.into();
+        self
+    }
+
+    pub fn into_inner(self) -> W {
+        self.output
+    }
+
+    pub fn print_program(&mut self, program: &Program) -> Result {
+        self.print_statements(&program.statements, usize::MAX, false)
+    }
+
+    pub fn print_statement(&mut self, statement: &Statement) -> Result {
+        match statement {
+            Statement::Let(let_statement) => self.print_let_statement(let_statement),
+            Statement::Return(return_statement) => self.print_return_statement(return_statement),
+            Statement::Expression(expression) => {
+                self.print_expression(expression)?;
+                self.write_char(';')
+            }
+            Statement::Error(_) => self.write_str("<error>;"),
+        }
+    }
+
+    fn print_let_statement(&mut self, let_statement: &LetStatement) -> Result {
+        write!(self, "let {} = ", let_statement.identifier_name)?;
+        self.print_expression(&let_statement.value)?;
+        self.write_char(';')
+    }
+
+    fn print_return_statement(&mut self, return_statement: &ReturnStatement) -> Result {
+        self.write_str("return ")?;
+        self.print_expression(&return_statement.value)?;
+        self.write_char(';')
+    }
+
+    pub fn print_block_statement(&mut self, block: &BlockStatement) -> Result {
+        if block.statements.is_empty() && !self.has_comment_before(block.span.end) {
+            return self.write_str("{}");
+        }
+
+        self.write_char('{')?;
+        self.indent += 1;
+        self.print_statements(&block.statements, block.span.end, true)?;
+        self.indent -= 1;
+        self.new_line()?;
+        self.write_char('}')
+    }
+
+    /// Prints one statement per line, along with the comments that come before `end`. Lines are
+    /// separated by newlines, and the first line is preceded by one only if `break_first` is set.
+    fn print_statements(&mut self, statements: &[Statement], end: usize, mut break_first: bool) -> Result {
+        let mut previous_end = None;
+
+        for (index, statement) in statements.iter().enumerate() {
+            let span = statement.span();
+            while let Some(comment) = self.next_comment_before(span.start) {
+                self.start_line(&mut break_first, previous_end, comment.span.start)?;
+                self.write_str(&comment.text)?;
+                previous_end = Some(comment.span.end);
+            }
+
+            self.start_line(&mut break_first, previous_end, span.start)?;
+            self.print_statement(statement)?;
+            previous_end = Some(span.end);
+
+            let next_start = statements.get(index + 1).map_or(end, |next| next.span().start);
+            while self.comments.front().is_some_and(|comment| comment.span.start < next_start && self.is_trailing(comment)) {
+                let comment = self.comments.pop_front().unwrap();
+                write!(self, " {}", comment.text)?;
+                previous_end = Some(comment.span.end);
+            }
+        }
+
+        while let Some(comment) = self.next_comment_before(end) {
+            self.start_line(&mut break_first, previous_end, comment.span.start)?;
+            self.write_str(&comment.text)?;
+            previous_end = Some(comment.span.end);
+        }
+        Ok(())
+    }
+
+    fn start_line(&mut self, break_first: &mut bool, previous_end: Option<usize>, start: usize) -> Result {
+        if let Some(previous_end) = previous_end {
+            if self.source.get(previous_end..start).is_some_and(|between| between.matches('\n').count() > 1) {
+                self.write_char('\n')?;
+            }
+        } else if !*break_first {
+            *break_first = true;
+            return self.write_str(&INDENT.repeat(self.indent));
+        }
+        self.new_line()
+    }
+
+    fn next_comment_before(&mut self, offset: usize) -> Option<Trivia> {
+        match self.comments.front() {
+            Some(comment) if comment.span.start < offset => self.comments.pop_front(),
+            _ => None,
+        }
+    }
+
+    fn has_comment_before(&self, offset: usize) -> bool {
+        self.comments.front().is_some_and(|comment| comment.span.start < offset)
+    }
+
+    /// Whether code comes before `comment` on its line in the source.
+    fn is_trailing(&self, comment: &Trivia) -> bool {
+        let line_start = self.source[..comment.span.start].rfind('\n').map_or(0, |index| index + 1);
+        !self.source[line_start..comment.span.start].trim().is_empty()
+    }
+
+    pub fn print_expression(&mut self, expression: &Expression) -> Result {
+        while let Some(comment) = self.next_comment_before(expression.span().start) {
+            self.write_str(&comment.text)?;
+            match comment.kind {
+                TriviaKind::LineComment => {
+                    self.indent += 1;
+                    self.new_line()?;
+                    self.indent -= 1;
+                }
+                TriviaKind::BlockComment => self.write_char(' ')?,
+            }
+        }
+
+        match expression {
+            Expression::Identifier { token, .. } => write!(self, "{}", token),
+            Expression::Boolean { value, .. } => write!(self, "{}", value),
+            Expression::IntegerLiteral { value, .. } => write!(self, "{}", value),
+            Expression::StringLiteral { value, .. } => self.print_string_literal(value),
+            Expression::PrefixExpression { operator, right, .. } => {
+                self.open_parenthesis()?;
+                self.write_str(operator)?;
+                self.print_operand(right, precedence_of(right) < prefix_precedence(operator))?;
+                self.close_parenthesis()
+            }
+            Expression::InfixExpression { operator, left, right, .. } => {
+                let precedence = infix_precedence(operator).value();
+                let right_associative = operator::infix_operator(operator)
+                    .is_some_and(|infix| infix.associativity == Associativity::Right);
+                let (left_precedence, right_precedence) = (precedence_of(left).value(), precedence_of(right).value());
+                self.open_parenthesis()?;
+                self.print_operand(left, left_precedence < precedence || (right_associative && left_precedence == precedence))?;
+                write!(self, " {} ", operator)?;
+                self.print_operand(right, right_precedence < precedence || (!right_associative && right_precedence == precedence))?;
+                self.close_parenthesis()
+            }
+            Expression::IfExpression { condition, consequence, alternative, .. } => {
+                self.write_str("if (")?;
+                self.print_expression(condition)?;
+                self.write_str(") ")?;
+                match consequence {
+                    Some(consequence) => self.print_block_statement(consequence)?,
+                    None => self.write_str("{}")?,
+                }
+                if let Some(alternative) = alternative {
+                    self.write_str(" else ")?;
+                    self.print_block_statement(alternative)?;
+                }
+                Ok(())
+            }
+            Expression::Function { parameters, body, .. } => {
+                self.write_str("fn")?;
+                self.print_list('(', parameters, ')', body.span.start)?;
+                self.write_char(' ')?;
+                self.print_block_statement(body)
+            }
+            Expression::Call { function, arguments, span } => {
+                self.print_operand(function, precedence_of(function).value() < Precedence::Call.value())?;
+                self.print_list('(', arguments, ')', span.end)
+            }
+            Expression::ArrayLiteral { elements, span } => self.print_list('[', elements, ']', span.end),
+            Expression::Index { left, index, .. } => {
+                self.open_parenthesis()?;
+                self.print_operand(left, precedence_of(left).value() < Precedence::Call.value())?;
+                self.write_char('[')?;
+                self.print_expression(index)?;
+                self.write_char(']')?;
+                self.close_parenthesis()
+            }
+            Expression::HashLiteral { pairs, span } => self.print_list('{', pairs, '}', span.end),
+            Expression::Error { .. } => self.write_str("<error>"),
+        }
+    }
+
+    fn print_operand(&mut self, operand: &Expression, needs_parentheses: bool) -> Result {
+        if !needs_parentheses || self.parenthesize {
+            return self.print_expression(operand);
+        }
+        self.write_char('(')?;
+        self.print_expression(operand)?;
+        self.write_char(')')
+    }
+
+    /// Prints `items` separated by commas between `open` and `close`, which ends at `end` in the
+    /// source. The list goes on one line if the first line of that fits within the line width and
+    /// it holds no comments, and otherwise each item and each comment goes on its own line, except
+    /// that a comment which followed code on its line stays at the end of that line.
+    fn print_list<T: ListItem>(&mut self, open: char, items: &[T], close: char, end: usize) -> Result {
+        let has_comments = self.has_comment_before(end);
+        if !has_comments && (items.is_empty() || self.fits_on_line(open, items, close)) {
+            self.write_char(open)?;
+            for (index, item) in items.iter().enumerate() {
+                if index > 0 {
+                    self.write_str(", ")?;
+                }
+                item.print(self)?;
+            }
+            return self.write_char(close);
+        }
+
+        self.write_char(open)?;
+        self.indent += 1;
+        for (index, item) in items.iter().enumerate() {
+            self.print_list_comments(item.span().start)?;
+            self.new_line()?;
+            item.print(self)?;
+            if index + 1 < items.len() {
+                self.write_char(',')?;
+            }
+
+            let next_start = items.get(index + 1).map_or(end, |next| next.span().start);
+            while self.comments.front().is_some_and(|comment| comment.span.start < next_start && self.is_trailing(comment)) {
+                let comment = self.comments.pop_front().unwrap();
+                write!(self, " {}", comment.text)?;
+            }
+        }
+        self.print_list_comments(end)?;
+        self.indent -= 1;
+        self.new_line()?;
+        self.write_char(close)
+    }
+
+    /// Prints the comments in a list that come before `offset`, each on its own line unless it
+    /// followed code on its line.
+    fn print_list_comments(&mut self, offset: usize) -> Result {
+        while let Some(comment) = self.next_comment_before(offset) {
+            match self.is_trailing(&comment) {
+                true => write!(self, " {}", comment.text)?,
+                false => {
+                    self.new_line()?;
+                    self.write_str(&comment.text)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn fits_on_line<T: ListItem>(&self, open: char, items: &[T], close: char) -> bool {
+        let line_width = match self.line_width {
+            Some(line_width) => line_width,
+            None => return true,
+        };
+
+        let mut flat = new(String::new(), self.parenthesize);
+        flat.indent = self.indent;
+        flat.column = self.column;
+        let _ = flat.print_list(open, items, close, 0);
+        let first_line = flat.output.lines().next().unwrap_or("");
+        self.column + first_line.chars().count() <= line_width
+    }
+
+    fn print_string_literal(&mut self, value: &str) -> Result {
+        self.write_char('"')?;
+        for character in value.chars() {
+            match character {
+                '"' => self.write_str("\\\"")?,
+                '\\' => self.write_str("\\\\")?,
+                '\n' => self.write_str("\\n")?,
+                '\t' => self.write_str("\\t")?,
+                '\r' => self.write_str("\\r")?,
+                '\0' => self.write_str("\\0")?,
+                character if character.is_control() => write!(self, "\\u{{{:x}}}", character as u32)?,
+                character => self.write_char(character)?,
+            }
+        }
+        self.write_char('"')
+    }
+
+    fn open_parenthesis(&mut self) -> Result {
+        match self.parenthesize {
+            true => self.write_char('('),
+            false => Ok(()),
+        }
+    }
+
+    fn close_parenthesis(&mut self) -> Result {
+        match self.parenthesize {
+            true => self.write_char(')'),
+            false => Ok(()),
+        }
+    }
+
+    fn new_line(&mut self) -> Result {
+        self.write_char('\n')?;
+        for _ in 0..self.indent {
+            self.write_str(INDENT)?;
+        }
+        Ok(())
+    }
+}
+
+/// How tightly an expression binds, as seen by the operator it is an operand of. Literals and
+/// other self-delimiting expressions never need parentheses.
+fn precedence_of(expression: &Expression) -> Precedence {
+    match expression {
+        Expression::PrefixExpression { operator, .. } => prefix_precedence(operator),
+        Expression::InfixExpression { operator, .. } => infix_precedence(operator),
+        Expression::Call { .. } => Precedence::Call,
+        _ => Precedence::Index,
+    }
+}
+
+fn prefix_precedence(operator: &str) -> Precedence {
+    operator::prefix_operator(operator).map_or(Precedence::Lowest, |prefix| prefix.precedence)
+}
+
+fn infix_precedence(operator: &str) -> Precedence {
+    operator::infix_operator(operator).map_or(Precedence::Lowest, |infix| infix.precedence)
+}
@@ -0,0 +1,36 @@
+use crate::ast::visit::{self, Visitor};
+use crate::ast::{Expression, LetStatement, Statement};
+
+/// The names that `statements` bind with `let`, in the order they are first bound. Blocks do not
+/// open a scope of their own, so the `let`s in the branches of an `if` count, while those in a
+/// function literal belong to that function.
+///
+/// A function's body is its scope: a name bound anywhere in it refers to that binding everywhere
+/// in the body, including in functions defined before the `let` runs. The evaluator declares these
+/// names when a function is called, and the compiler gives them slots before compiling the body.
+pub fn let_names(statements: &[Statement]) -> Vec<String> {
+    let mut collector = LetNames { names: Vec::new() };
+    for statement in statements.iter() {
+        collector.visit_statement(statement);
+    }
+    return collector.names;
+}
+
+struct LetNames {
+    names: Vec<String>,
+}
+
+impl Visitor for LetNames {
+    fn visit_let_statement(&mut self, let_statement: &LetStatement) {
+        if !self.names.contains(&let_statement.identifier_name) {
+            self.names.push(let_statement.identifier_name.clone());
+        }
+        visit::walk_let_statement(self, let_statement);
+    }
+
+    fn visit_expression(&mut self, expression: &Expression) {
+        if !matches!(expression, Expression::Function { .. }) {
+            visit::walk_expression(self, expression);
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use crate::ast::{BlockStatement, Expression, LetStatement, Program, ReturnStatement, Statement};
+
+/// Walks a syntax tree by reference. Each method's default implementation visits the node's
+/// children through the matching `walk_*` function, so an implementation only overrides the nodes
+/// it is interested in, and calls the `walk_*` function itself if it still wants to descend.
+pub trait Visitor: Sized {
+    fn visit_program(&mut self, program: &Program) {
+        walk_program(self, program)
+    }
+
+    fn visit_statement(&mut self, statement: &Statement) {
+        walk_statement(self, statement)
+    }
+
+    fn visit_let_statement(&mut self, let_statement: &LetStatement) {
+        walk_let_statement(self, let_statement)
+    }
+
+    fn visit_return_statement(&mut self, return_statement: &ReturnStatement) {
+        walk_return_statement(self, return_statement)
+    }
+
+    fn visit_block_statement(&mut self, block: &BlockStatement) {
+        walk_block_statement(self, block)
+    }
+
+    fn visit_expression(&mut self, expression: &Expression) {
+        walk_expression(self, expression)
+    }
+}
+
+pub fn walk_program<V: Visitor>(visitor: &mut V, program: &Program) {
+    for statement in program.statements.iter() {
+        visitor.visit_statement(statement);
+    }
+}
+
+pub fn walk_statement<V: Visitor>(visitor: &mut V, statement: &Statement) {
+    match statement {
+        Statement::Let(let_statement) => visitor.visit_let_statement(let_statement),
+        Statement::Return(return_statement) => visitor.visit_return_statement(return_statement),
+        Statement::Expression(expression) => visitor.visit_expression(expression),
+        Statement::Error(_) => {}
+    }
+}
+
+pub fn walk_let_statement<V: Visitor>(visitor: &mut V, let_statement: &LetStatement) {
+    visitor.visit_expression(&let_statement.value);
+}
+
+pub fn walk_return_statement<V: Visitor>(visitor: &mut V, return_statement: &ReturnStatement) {
+    visitor.visit_expression(&return_statement.value);
+}
+
+pub fn walk_block_statement<V: Visitor>(visitor: &mut V, block: &BlockStatement) {
+    for statement in block.statements.iter() {
+        visitor.visit_statement(statement);
+    }
+}
+
+pub fn walk_expression<V: Visitor>(visitor: &mut V, expression: &Expression) {
+    match expression {
+        Expression::Identifier { .. }
+        | Expression::Boolean { .. }
+        | Expression::IntegerLiteral { .. }
+        | Expression::StringLiteral { .. }
+        | Expression::Error { .. } => {}
+        Expression::PrefixExpression { right, .. } => visitor.visit_expression(right),
+        Expression::InfixExpression { left, right, .. } => {
+            visitor.visit_expression(left);
+            visitor.visit_expression(right);
+        }
+        Expression::IfExpression { condition, consequence, alternative, .. } => {
+            visitor.visit_expression(condition);
+            if let Some(consequence) = consequence {
+                visitor.visit_block_statement(consequence);
+            }
+            if let Some(alternative) = alternative {
+                visitor.visit_block_statement(alternative);
+            }
+        }
+        Expression::Function { parameters, body, .. } => {
+            for parameter in parameters.iter() {
+                visitor.visit_expression(parameter);
+            }
+            visitor.visit_block_statement(body);
+        }
+        Expression::Call { function, arguments, .. } => {
+            visitor.visit_expression(function);
+            for argument in arguments.iter() {
+                visitor.visit_expression(argument);
+            }
+        }
+        Expression::ArrayLiteral { elements, .. } => {
+            for element in elements.iter() {
+                visitor.visit_expression(element);
+            }
+        }
+        Expression::Index { left, index, .. } => {
+            visitor.visit_expression(left);
+            visitor.visit_expression(index);
+        }
+        Expression::HashLiteral { pairs, .. } => {
+            for (key, value) in pairs.iter() {
+                visitor.visit_expression(key);
+                visitor.visit_expression(value);
+            }
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use crate::ast::{BlockStatement, Expression, LetStatement, Program, ReturnStatement, Statement};
+
+/// Walks a syntax tree by mutable reference so that nodes can be changed in place. Like
+/// `Visitor`, each default method descends through the matching `walk_*_mut` function.
+pub trait VisitorMut: Sized {
+    fn visit_program(&mut self, program: &mut Program) {
+        walk_program_mut(self, program)
+    }
+
+    fn visit_statement(&mut self, statement: &mut Statement) {
+        walk_statement_mut(self, statement)
+    }
+
+    fn visit_let_statement(&mut self, let_statement: &mut LetStatement) {
+        walk_let_statement_mut(self, let_statement)
+    }
+
+    fn visit_return_statement(&mut self, return_statement: &mut ReturnStatement) {
+        walk_return_statement_mut(self, return_statement)
+    }
+
+    fn visit_block_statement(&mut self, block: &mut BlockStatement) {
+        walk_block_statement_mut(self, block)
+    }
+
+    fn visit_expression(&mut self, expression: &mut Expression) {
+        walk_expression_mut(self, expression)
+    }
+}
+
+pub fn walk_program_mut<V: VisitorMut>(visitor: &mut V, program: &mut Program) {
+    for statement in program.statements.iter_mut() {
+        visitor.visit_statement(statement);
+    }
+}
+
+pub fn walk_statement_mut<V: VisitorMut>(visitor: &mut V, statement: &mut Statement) {
+    match statement {
+        Statement::Let(let_statement) => visitor.visit_let_statement(let_statement),
+        Statement::Return(return_statement) => visitor.visit_return_statement(return_statement),
+        Statement::Expression(expression) => visitor.visit_expression(expression),
+        Statement::Error(_) => {}
+    }
+}
+
+pub fn walk_let_statement_mut<V: VisitorMut>(visitor: &mut V, let_statement: &mut LetStatement) {
+    visitor.visit_expression(&mut let_statement.value);
+}
+
+pub fn walk_return_statement_mut<V: VisitorMut>(visitor: &mut V, return_statement: &mut ReturnStatement) {
+    visitor.visit_expression(&mut return_statement.value);
+}
+
+pub fn walk_block_statement_mut<V: VisitorMut>(visitor: &mut V, block: &mut BlockStatement) {
+    for statement in block.statements.iter_mut() {
+        visitor.visit_statement(statement);
+    }
+}
+
+pub fn walk_expression_mut<V: VisitorMut>(visitor: &mut V, expression: &mut Expression) {
+    match expression {
+        Expression::Identifier { .. }
+        | Expression::Boolean { .. }
+        | Expression::IntegerLiteral { .. }
+        | Expression::StringLiteral { .. }
+        | Expression::Error { .. } => {}
+        Expression::PrefixExpression { right, .. } => visitor.visit_expression(right),
+        Expression::InfixExpression { left, right, .. } => {
+            visitor.visit_expression(left);
+            visitor.visit_expression(right);
+        }
+        Expression::IfExpression { condition, consequence, alternative, .. } => {
+            visitor.visit_expression(condition);
+            if let Some(consequence) = consequence {
+                visitor.visit_block_statement(consequence);
+            }
+            if let Some(alternative) = alternative {
+                visitor.visit_block_statement(alternative);
+            }
+        }
+        Expression::Function { parameters, body, .. } => {
+            for parameter in parameters.iter_mut() {
+                visitor.visit_expression(parameter);
+            }
+            visitor.visit_block_statement(body);
+        }
+        Expression::Call { function, arguments, .. } => {
+            visitor.visit_expression(function);
+            for argument in arguments.iter_mut() {
+                visitor.visit_expression(argument);
+            }
+        }
+        Expression::ArrayLiteral { elements, .. } => {
+            for element in elements.iter_mut() {
+                visitor.visit_expression(element);
+            }
+        }
+        Expression::Index { left, index, .. } => {
+            visitor.visit_expression(left);
+            visitor.visit_expression(index);
+        }
+        Expression::HashLiteral { pairs, .. } => {
+            for (key, value) in pairs.iter_mut() {
+                visitor.visit_expression(key);
+                visitor.visit_expression(value);
+            }
+        }
+    }
+}
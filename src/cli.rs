@@ -0,0 +1,357 @@
+use std::fs;
+use std::io::{BufRead, Write};
+use std::path::Path;
+use crate::ast::Program;
+use crate::compiler::{disassembler, file, Bytecode};
+use crate::diagnostic::{Diagnostic, Style};
+use crate::evaluator::environment;
+use crate::evaluator::object::Object;
+use crate::parser::error::ParseError;
+use crate::token::Token;
+use crate::{compiler, evaluator, formatter, lexer, optimizer, parser, repl, serialize, vm};
+
+pub mod exit_code {
+    pub const SUCCESS: i32 = 0;
+    pub const FAILURE: i32 = 1;
+    pub const USAGE: i32 = 2;
+}
+
+const USAGE: &str = "\
+usage: tarzan [<command> <file>...]
+
+commands:
+    run <file>                  evaluate a Monkey script, or run a bytecode file, and print
+                                its final value
+    tokens [--format <f>] <file>
+                                print the tokens of a Monkey script
+    ast [--format <f>] <file>   print the syntax tree of a Monkey script
+    check <file>                parse a Monkey script and report any errors and warnings
+    build <file> [-o <out>]     compile a Monkey script to a bytecode file, by default the
+                                script's path with the extension `.mkc`
+    disasm <file>               print the bytecode of a Monkey script or bytecode file
+    fmt [--check] <file>...     rewrite Monkey scripts in the canonical style, or with
+                                --check, fail if any of them are not already formatted
+
+<file> may be `-` to read from standard input; `fmt -` writes to standard output.
+<f> is `debug` (the default), `json` or `sexpr`.
+Without a command, an interactive REPL is started.";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum OutputFormat {
+    Debug,
+    Json,
+    SExpression,
+}
+
+struct Arguments<'a> {
+    paths: Vec<&'a str>,
+    check: bool,
+    format: Option<OutputFormat>,
+    output: Option<&'a str>,
+}
+
+fn parse_arguments(arguments: &[String]) -> Result<Arguments<'_>, String> {
+    let mut parsed = Arguments { paths: Vec::new(), check: false, format: None, output: None };
+    let mut arguments = arguments.iter();
+    while let Some(argument) = arguments.next() {
+        match argument.as_str() {
+            "--check" => parsed.check = true,
+            "--format" => {
+                parsed.format = match arguments.next().map(String::as_str) {
+                    Some("debug") => Some(OutputFormat::Debug),
+                    Some("json") => Some(OutputFormat::Json),
+                    Some("sexpr") => Some(OutputFormat::SExpression),
+                    Some(format) => return Err(format!("unknown format: {}", format)),
+                    None => return Err("missing value for --format".to_string()),
+                }
+            }
+            "-o" => match arguments.next() {
+                Some(output) => parsed.output = Some(output),
+                None => return Err("missing value for -o".to_string()),
+            },
+            "-" => parsed.paths.push(argument),
+            option if option.starts_with('-') => return Err(format!("unknown option: {}", option)),
+            path => parsed.paths.push(path),
+        }
+    }
+    return Ok(parsed);
+}
+
+/// Runs the `tarzan` command line with `args` (excluding the program name) and returns the exit code.
+/// Errors in Monkey source are reported as diagnostics rendered in `style`.
+pub fn run<R: BufRead, W: Write, E: Write>(args: &[String], mut stdin: R, stdout: &mut W, stderr: &mut E, style: Style) -> i32 {
+    let (command, arguments) = match args {
+        [] => {
+            return match repl::start_with_style(stdin, stdout, style) {
+                Ok(()) => exit_code::SUCCESS,
+                Err(err) => report(stderr, format_args!("{}", err), exit_code::FAILURE),
+            };
+        }
+        [command, arguments @ ..] => (command.as_str(), arguments),
+    };
+
+    let arguments = match parse_arguments(arguments) {
+        Ok(arguments) => arguments,
+        Err(message) => return report(stderr, format_args!("{}\n\n{}", message, USAGE), exit_code::USAGE),
+    };
+    if command == "fmt" && !arguments.paths.is_empty() && arguments.format.is_none() {
+        return fmt_command(&arguments, stdin, stdout, stderr, style);
+    }
+    let takes_format = matches!(command, "tokens" | "ast");
+    let takes_output = command == "build";
+    let path = match arguments.paths.as_slice() {
+        [path] if !arguments.check
+            && (takes_format || arguments.format.is_none())
+            && (takes_output || arguments.output.is_none()) => *path,
+        _ => return report(stderr, format_args!("{}", USAGE), exit_code::USAGE),
+    };
+    let format = arguments.format.unwrap_or(OutputFormat::Debug);
+
+    let input = match read_input(path, &mut stdin) {
+        Ok(input) => input,
+        Err(err) => return report(stderr, format_args!("could not read {}: {}", path, err), exit_code::USAGE),
+    };
+    let name = source_name(path);
+    if file::is_bytecode(&input) && matches!(command, "run" | "disasm") {
+        return bytecode_command(command, name, &input, stdout, stderr, style);
+    }
+    let source = match String::from_utf8(input) {
+        Ok(source) => source,
+        Err(err) => return report(stderr, format_args!("could not read {}: {}", path, err), exit_code::USAGE),
+    };
+
+    match command {
+        "run" => run_command(name, &source, stdout, stderr, style),
+        "build" => {
+            let output = match (arguments.output, path) {
+                (Some(output), _) => output.to_string(),
+                (None, "-") => return report(stderr, format_args!("building from standard input needs -o <out>"), exit_code::USAGE),
+                (None, path) => Path::new(path).with_extension("mkc").to_string_lossy().into_owned(),
+            };
+            build_command(name, &source, &output, stderr, style)
+        }
+        "tokens" => tokens_command(name, &source, format, stdout, stderr, style),
+        "ast" => ast_command(name, &source, format, stdout, stderr, style),
+        "disasm" => disasm_command(name, &source, stdout, stderr, style),
+        "check" => match optimize(name, &source, stderr, style) {
+            Ok(_) => exit_code::SUCCESS,
+            Err(code) => code,
+        },
+        _ => report(stderr, format_args!("unknown command: {}\n\n{}", command, USAGE), exit_code::USAGE),
+    }
+}
+
+fn run_command<W: Write, E: Write>(name: &str, source: &str, stdout: &mut W, stderr: &mut E, style: Style) -> i32 {
+    let program = match optimize(name, source, stderr, style) {
+        Ok(program) => program,
+        Err(code) => return code,
+    };
+
+    let mut environment = environment::new();
+    match evaluator::eval_with_output(&program, &mut environment, stdout) {
+        Object::Error(error) => report_diagnostic(stderr, Diagnostic::from(&error), name, source, style),
+        Object::Null => exit_code::SUCCESS,
+        result => report(stdout, format_args!("{}", result), exit_code::SUCCESS),
+    }
+}
+
+fn tokens_command<W: Write, E: Write>(name: &str, source: &str, format: OutputFormat, stdout: &mut W, stderr: &mut E, style: Style) -> i32 {
+    let mut lexer = lexer::new(source.to_string());
+    let mut tokens = Vec::new();
+    loop {
+        match lexer.next_token() {
+            Ok(spanned_token) => {
+                let at_end = spanned_token.token == Token::Eof;
+                tokens.push(spanned_token);
+                if at_end {
+                    break;
+                }
+            }
+            Err(error) => return report_diagnostic(stderr, Diagnostic::from(&error), name, source, style),
+        }
+    }
+
+    match format {
+        OutputFormat::Debug => {
+            for spanned_token in tokens.iter() {
+                let span = spanned_token.span;
+                let _ = writeln!(stdout, "{}:{} {:?}", span.line, span.column, spanned_token.token);
+            }
+        }
+        OutputFormat::Json => {
+            let _ = writeln!(stdout, "{}", serialize::json::tokens(&tokens));
+        }
+        OutputFormat::SExpression => {
+            let _ = writeln!(stdout, "{}", serialize::sexpr::tokens(&tokens));
+        }
+    }
+    return exit_code::SUCCESS;
+}
+
+/// Prints the syntax tree even when the source has errors, with the parts that failed to parse
+/// left as `Error` placeholders.
+fn ast_command<W: Write, E: Write>(name: &str, source: &str, format: OutputFormat, stdout: &mut W, stderr: &mut E, style: Style) -> i32 {
+    let mut parser = parser::new(lexer::new(source.to_string()));
+    let (program, errors) = parser.parse_with_recovery();
+
+    match format {
+        OutputFormat::Debug => {
+            for statement in program.statements.iter() {
+                let _ = writeln!(stdout, "{:#?}", statement);
+            }
+        }
+        OutputFormat::Json => {
+            let _ = writeln!(stdout, "{}", serialize::json::program(&program));
+        }
+        OutputFormat::SExpression => {
+            let _ = writeln!(stdout, "{}", serialize::sexpr::program(&program));
+        }
+    }
+    match errors.is_empty() {
+        true => exit_code::SUCCESS,
+        false => report_parse_errors(stderr, &errors, name, source, style),
+    }
+}
+
+fn build_command<E: Write>(name: &str, source: &str, output: &str, stderr: &mut E, style: Style) -> i32 {
+    let bytecode = match compile(name, source, stderr, style) {
+        Ok(bytecode) => bytecode,
+        Err(code) => return code,
+    };
+
+    match fs::write(output, file::write(&bytecode)) {
+        Ok(()) => exit_code::SUCCESS,
+        Err(err) => report(stderr, format_args!("could not write {}: {}", output, err), exit_code::FAILURE),
+    }
+}
+
+/// Runs or disassembles a file written by `build`. Without the source at hand, runtime errors
+/// are reported with their location only.
+fn bytecode_command<W: Write, E: Write>(command: &str, name: &str, input: &[u8], stdout: &mut W, stderr: &mut E, style: Style) -> i32 {
+    let bytecode = match file::read(input) {
+        Ok(bytecode) => bytecode,
+        Err(err) => return report(stderr, format_args!("could not load {}: {}", name, err), exit_code::FAILURE),
+    };
+    if command == "disasm" {
+        let _ = write!(stdout, "{}", disassembler::disassemble(&bytecode, None));
+        return exit_code::SUCCESS;
+    }
+
+    match vm::run_with_output(&bytecode, stdout) {
+        Ok(Object::Null) => exit_code::SUCCESS,
+        Ok(result) => report(stdout, format_args!("{}", result), exit_code::SUCCESS),
+        Err(error) => {
+            let diagnostic = Diagnostic { span: None, ..Diagnostic::from(&error) };
+            let name = match error.span {
+                Some(span) => format!("{}:{}:{}", name, span.line, span.column),
+                None => name.to_string(),
+            };
+            report_diagnostic(stderr, diagnostic, &name, "", style)
+        }
+    }
+}
+
+fn disasm_command<W: Write, E: Write>(name: &str, source: &str, stdout: &mut W, stderr: &mut E, style: Style) -> i32 {
+    match compile(name, source, stderr, style) {
+        Ok(bytecode) => {
+            let _ = write!(stdout, "{}", disassembler::disassemble(&bytecode, Some(source)));
+            exit_code::SUCCESS
+        }
+        Err(code) => code,
+    }
+}
+
+fn fmt_command<R: BufRead, W: Write, E: Write>(arguments: &Arguments, mut stdin: R, stdout: &mut W, stderr: &mut E, style: Style) -> i32 {
+    let mut code = exit_code::SUCCESS;
+
+    for path in arguments.paths.iter() {
+        let source = match read_source(path, &mut stdin) {
+            Ok(source) => source,
+            Err(err) => return report(stderr, format_args!("could not read {}: {}", path, err), exit_code::USAGE),
+        };
+        let name = source_name(path);
+
+        let formatted = match formatter::format(&source) {
+            Ok(formatted) => formatted,
+            Err(errors) => {
+                code = report_parse_errors(stderr, &errors, name, &source, style);
+                continue;
+            }
+        };
+
+        if arguments.check {
+            if formatted != source {
+                code = report(stderr, format_args!("{}: not formatted", name), exit_code::FAILURE);
+            }
+        } else if *path == "-" {
+            let _ = write!(stdout, "{}", formatted);
+        } else if formatted != source {
+            if let Err(err) = fs::write(path, formatted) {
+                return report(stderr, format_args!("could not write {}: {}", path, err), exit_code::FAILURE);
+            }
+        }
+    }
+    return code;
+}
+
+fn parse<E: Write>(name: &str, source: &str, stderr: &mut E, style: Style) -> Result<Program, i32> {
+    let mut parser = parser::new(lexer::new(source.to_string()));
+    parser.parse().map_err(|errors| report_parse_errors(stderr, &errors, name, source, style))
+}
+
+/// Parses and optimizes `source`, reporting the warnings the optimizer finds.
+fn optimize<E: Write>(name: &str, source: &str, stderr: &mut E, style: Style) -> Result<Program, i32> {
+    let (program, warnings) = optimizer::optimize(parse(name, source, stderr, style)?);
+    for warning in warnings.iter() {
+        report_diagnostic(stderr, Diagnostic::from(warning), name, source, style);
+    }
+    return Ok(program);
+}
+
+/// Parses, optimizes and compiles `source`, reporting any warnings found along the way.
+fn compile<E: Write>(name: &str, source: &str, stderr: &mut E, style: Style) -> Result<Bytecode, i32> {
+    let program = optimize(name, source, stderr, style)?;
+    compiler::compile(&program).map_err(|error| report_diagnostic(stderr, Diagnostic::from(&error), name, source, style))
+}
+
+fn report_parse_errors<E: Write>(stderr: &mut E, errors: &[ParseError], name: &str, source: &str, style: Style) -> i32 {
+    for error in errors.iter() {
+        report_diagnostic(stderr, Diagnostic::from(error), name, source, style);
+    }
+    return exit_code::FAILURE;
+}
+
+fn report_diagnostic<E: Write>(stderr: &mut E, diagnostic: Diagnostic, name: &str, source: &str, style: Style) -> i32 {
+    let _ = write!(stderr, "{}", diagnostic.render(name, source, style));
+    return exit_code::FAILURE;
+}
+
+fn read_source<R: BufRead>(path: &str, stdin: &mut R) -> std::io::Result<String> {
+    if path == "-" {
+        let mut source = String::new();
+        stdin.read_to_string(&mut source)?;
+        return Ok(source);
+    }
+    fs::read_to_string(path)
+}
+
+fn read_input<R: BufRead>(path: &str, stdin: &mut R) -> std::io::Result<Vec<u8>> {
+    if path == "-" {
+        let mut input = Vec::new();
+        stdin.read_to_end(&mut input)?;
+        return Ok(input);
+    }
+    fs::read(path)
+}
+
+fn source_name(path: &str) -> &str {
+    match path {
+        "-" => "<stdin>",
+        _ => path,
+    }
+}
+
+fn report<W: Write>(output: &mut W, message: std::fmt::Arguments, code: i32) -> i32 {
+    let _ = writeln!(output, "{}", message);
+    return code;
+}
@@ -0,0 +1,178 @@
+/// The instruction set of the virtual machine. An instruction is one opcode byte followed by its
+/// operands, each a big-endian unsigned integer of the width given by `Opcode::operand_widths`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum Opcode {
+    /// Pushes the constant at the operand's index in the constant pool.
+    Constant,
+    /// Discards the top of the stack.
+    Pop,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Equal,
+    NotEqual,
+    LessThan,
+    GreaterThan,
+    /// Prefix `-`.
+    Negate,
+    /// Prefix `!`.
+    Not,
+    /// Prefix `+`, which only checks that its operand is an integer.
+    Positive,
+    True,
+    False,
+    Null,
+    /// Continues at the operand's offset.
+    Jump,
+    /// Pops the condition and continues at the operand's offset if it is falsy.
+    JumpNotTruthy,
+    GetGlobal,
+    SetGlobal,
+    GetLocal,
+    SetLocal,
+    /// Pushes the value of the operand'th variable shared with the running closure.
+    GetFree,
+    /// Pushes the builtin function at the operand's index in `builtins::BUILTINS`.
+    GetBuiltin,
+    /// Pops the operand's number of elements into an array.
+    Array,
+    /// Fails unless the value on top of the stack can be a hash key, leaving it there.
+    CheckHashKey,
+    /// Pops the operand's number of keys and values, alternating, into a hash.
+    Hash,
+    Index,
+    /// Calls the function below the operand's number of arguments on the stack.
+    Call,
+    /// Pops the return value, leaves the current call and pushes the value onto the caller's stack.
+    Return,
+    /// Creates a closure of the function prototype at the operand's index that shares the
+    /// variables listed in the prototype's `free` with the running function.
+    Closure,
+}
+
+const OPCODES: &[Opcode] = &[
+    Opcode::Constant,
+    Opcode::Pop,
+    Opcode::Add,
+    Opcode::Subtract,
+    Opcode::Multiply,
+    Opcode::Divide,
+    Opcode::Equal,
+    Opcode::NotEqual,
+    Opcode::LessThan,
+    Opcode::GreaterThan,
+    Opcode::Negate,
+    Opcode::Not,
+    Opcode::Positive,
+    Opcode::True,
+    Opcode::False,
+    Opcode::Null,
+    Opcode::Jump,
+    Opcode::JumpNotTruthy,
+    Opcode::GetGlobal,
+    Opcode::SetGlobal,
+    Opcode::GetLocal,
+    Opcode::SetLocal,
+    Opcode::GetFree,
+    Opcode::GetBuiltin,
+    Opcode::Array,
+    Opcode::CheckHashKey,
+    Opcode::Hash,
+    Opcode::Index,
+    Opcode::Call,
+    Opcode::Return,
+    Opcode::Closure,
+];
+
+impl Opcode {
+    pub fn from_byte(byte: u8) -> Option<Opcode> {
+        OPCODES.get(byte as usize).copied()
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Opcode::Constant => "Constant",
+            Opcode::Pop => "Pop",
+            Opcode::Add => "Add",
+            Opcode::Subtract => "Subtract",
+            Opcode::Multiply => "Multiply",
+            Opcode::Divide => "Divide",
+            Opcode::Equal => "Equal",
+            Opcode::NotEqual => "NotEqual",
+            Opcode::LessThan => "LessThan",
+            Opcode::GreaterThan => "GreaterThan",
+            Opcode::Negate => "Negate",
+            Opcode::Not => "Not",
+            Opcode::Positive => "Positive",
+            Opcode::True => "True",
+            Opcode::False => "False",
+            Opcode::Null => "Null",
+            Opcode::Jump => "Jump",
+            Opcode::JumpNotTruthy => "JumpNotTruthy",
+            Opcode::GetGlobal => "GetGlobal",
+            Opcode::SetGlobal => "SetGlobal",
+            Opcode::GetLocal => "GetLocal",
+            Opcode::SetLocal => "SetLocal",
+            Opcode::GetFree => "GetFree",
+            Opcode::GetBuiltin => "GetBuiltin",
+            Opcode::Array => "Array",
+            Opcode::CheckHashKey => "CheckHashKey",
+            Opcode::Hash => "Hash",
+            Opcode::Index => "Index",
+            Opcode::Call => "Call",
+            Opcode::Return => "Return",
+            Opcode::Closure => "Closure",
+        }
+    }
+
+    /// The width in bytes of each of the opcode's operands.
+    pub fn operand_widths(&self) -> &'static [usize] {
+        match self {
+            Opcode::Constant
+            | Opcode::Jump
+            | Opcode::JumpNotTruthy
+            | Opcode::GetGlobal
+            | Opcode::SetGlobal
+            | Opcode::Array
+            | Opcode::Hash
+            | Opcode::Closure => &[2],
+            Opcode::GetLocal | Opcode::SetLocal | Opcode::GetFree | Opcode::GetBuiltin | Opcode::Call => &[1],
+            _ => &[],
+        }
+    }
+}
+
+/// Encodes one instruction. Operands that do not fit their width are truncated, so callers check
+/// their limits first.
+pub fn make(opcode: Opcode, operands: &[usize]) -> Vec<u8> {
+    let mut instruction = vec![opcode as u8];
+    for (operand, width) in operands.iter().zip(opcode.operand_widths()) {
+        match width {
+            2 => instruction.extend_from_slice(&(*operand as u16).to_be_bytes()),
+            _ => instruction.push(*operand as u8),
+        }
+    }
+    return instruction;
+}
+
+/// Decodes the operands of `opcode` from the bytes that follow it, returning them with the number of
+/// bytes read, or `None` if `instructions` ends before they do.
+pub fn read_operands(opcode: Opcode, instructions: &[u8]) -> Option<(Vec<usize>, usize)> {
+    let mut operands = Vec::with_capacity(opcode.operand_widths().len());
+    let mut offset = 0;
+    for width in opcode.operand_widths() {
+        let bytes = instructions.get(offset..offset + width)?;
+        operands.push(match width {
+            2 => read_u16(bytes) as usize,
+            _ => bytes[0] as usize,
+        });
+        offset += width;
+    }
+    return Some((operands, offset));
+}
+
+pub fn read_u16(bytes: &[u8]) -> u16 {
+    u16::from_be_bytes([bytes[0], bytes[1]])
+}
@@ -0,0 +1,114 @@
+use std::fmt::Write;
+use crate::compiler::code::{self, Opcode};
+use crate::compiler::{Bytecode, CompiledFunction};
+use crate::evaluator::builtins::BUILTINS;
+
+/// Renders `bytecode` as text for debugging: the constant pool, the global names, and then the
+/// instructions of the program and of each function. Every instruction is shown with its offset,
+/// the source line it was compiled from, its opcode and its decoded operands, followed by what the
+/// operands refer to where that is known:
+///
+/// ```text
+/// constants:
+///     0  1
+///
+/// globals:
+///     0  x
+///
+/// main:
+///   0000     1  Constant 0               ; 1
+///   0003     1  SetGlobal 0              ; x
+/// ```
+///
+/// With `source`, the text of each source line is printed above the instructions compiled from it.
+pub fn disassemble(bytecode: &Bytecode, source: Option<&str>) -> String {
+    let mut output = String::new();
+
+    let _ = writeln!(output, "constants:");
+    for (index, constant) in bytecode.constants.iter().enumerate() {
+        let _ = writeln!(output, "{:>5}  {}", index, constant);
+    }
+    let _ = writeln!(output, "\nglobals:");
+    for (index, name) in bytecode.globals.iter().enumerate() {
+        let _ = writeln!(output, "{:>5}  {}", index, name);
+    }
+
+    let _ = writeln!(output, "\nmain:");
+    disassemble_function(&mut output, bytecode, &bytecode.main, source);
+    for (index, function) in bytecode.functions.iter().enumerate() {
+        let free: Vec<&str> = function.free.iter().map(|symbol| symbol.name.as_str()).collect();
+        let _ = writeln!(
+            output,
+            "\nfunction {} {}({}), {} locals{}:",
+            index,
+            function.name.as_deref().unwrap_or("<anonymous>"),
+            function.parameters.join(", "),
+            function.locals.len(),
+            if free.is_empty() { String::new() } else { format!(", free {}", free.join(", ")) }
+        );
+        disassemble_function(&mut output, bytecode, function, source);
+    }
+    return output;
+}
+
+fn disassemble_function(output: &mut String, bytecode: &Bytecode, function: &CompiledFunction, source: Option<&str>) {
+    let instructions = &function.instructions;
+    let mut previous_line = None;
+    let mut offset = 0;
+
+    while offset < instructions.len() {
+        // An empty program has no source to point at, so its instructions have line 0.
+        let line = function.span_at(offset).map(|span| span.line).filter(|line| *line > 0);
+        if let (Some(source), Some(line)) = (source, line) {
+            if previous_line != Some(line) {
+                let text = source.lines().nth(line - 1).unwrap_or("");
+                let _ = writeln!(output, "{:>12} | {}", line, text.trim_end());
+            }
+        }
+        previous_line = line;
+        let line = line.map_or("-".to_string(), |line| line.to_string());
+
+        let opcode = match Opcode::from_byte(instructions[offset]) {
+            Some(opcode) => opcode,
+            None => {
+                let _ = writeln!(output, "  {:04}  {:>4}  <invalid opcode {:#04x}>", offset, line, instructions[offset]);
+                offset += 1;
+                continue;
+            }
+        };
+        let (operands, width) = match code::read_operands(opcode, &instructions[offset + 1..]) {
+            Some(decoded) => decoded,
+            None => {
+                let _ = writeln!(output, "  {:04}  {:>4}  <truncated {}>", offset, line, opcode.name());
+                return;
+            }
+        };
+
+        let mut text = opcode.name().to_string();
+        for operand in operands.iter() {
+            let _ = write!(text, " {}", operand);
+        }
+        match describe(bytecode, opcode, &operands) {
+            Some(description) => {
+                let _ = writeln!(output, "  {:04}  {:>4}  {:<24} ; {}", offset, line, text, description);
+            }
+            None => {
+                let _ = writeln!(output, "  {:04}  {:>4}  {}", offset, line, text);
+            }
+        }
+        offset += 1 + width;
+    }
+}
+
+/// What the operands of an instruction refer to, for the instructions that refer to something.
+fn describe(bytecode: &Bytecode, opcode: Opcode, operands: &[usize]) -> Option<String> {
+    match opcode {
+        Opcode::Constant => bytecode.constants.get(operands[0]).map(|constant| constant.to_string()),
+        Opcode::GetGlobal | Opcode::SetGlobal => bytecode.globals.get(operands[0]).cloned(),
+        Opcode::GetBuiltin => BUILTINS.get(operands[0]).map(|builtin| builtin.name().to_string()),
+        Opcode::Closure => bytecode.functions.get(operands[0]).map(|function| {
+            format!("function {} {}", operands[0], function.name.as_deref().unwrap_or("<anonymous>"))
+        }),
+        _ => None,
+    }
+}
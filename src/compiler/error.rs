@@ -0,0 +1,74 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use crate::token::Span;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CompileError {
+    /// The program still has a placeholder left by parser error recovery.
+    SyntaxError { span: Span },
+    UnknownOperator { operator: String, span: Span },
+    /// A table or operand outgrew what the instruction encoding can address.
+    LimitExceeded { what: &'static str, limit: usize, span: Span },
+}
+
+impl CompileError {
+    pub fn span(&self) -> Span {
+        match self {
+            CompileError::SyntaxError { span }
+            | CompileError::UnknownOperator { span, .. }
+            | CompileError::LimitExceeded { span, .. } => *span,
+        }
+    }
+
+    /// The error without its location.
+    pub fn message(&self) -> String {
+        match self {
+            CompileError::SyntaxError { .. } => "syntax error".to_string(),
+            CompileError::UnknownOperator { operator, .. } => format!("unknown operator: {}", operator),
+            CompileError::LimitExceeded { what, limit, .. } => format!("too many {}: the limit is {}", what, limit),
+        }
+    }
+}
+
+impl Display for CompileError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let span = self.span();
+        write!(f, "{} at {}:{}", self.message(), span.line, span.column)
+    }
+}
+
+impl Error for CompileError {}
+
+/// Why a bytecode file could not be loaded.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LoadError {
+    /// The file does not start with the bytecode magic number.
+    NotBytecode,
+    /// The file was written in a format version this build cannot read.
+    UnsupportedVersion { found: u16, expected: u16 },
+    /// The contents do not match the checksum stored with them.
+    ChecksumMismatch,
+    /// The file ends in the middle of the data it describes.
+    Truncated,
+    /// The file is well-formed but describes bytecode the VM cannot run, such as an instruction
+    /// referring to a constant that does not exist.
+    Malformed(String),
+}
+
+impl Display for LoadError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoadError::NotBytecode => write!(f, "not a tarzan bytecode file"),
+            LoadError::UnsupportedVersion { found, expected } => write!(
+                f,
+                "bytecode format version {} is not supported, expected version {}; rebuild the script with `tarzan build`",
+                found, expected
+            ),
+            LoadError::ChecksumMismatch => write!(f, "bytecode checksum mismatch, the file is corrupt"),
+            LoadError::Truncated => write!(f, "bytecode file ends unexpectedly"),
+            LoadError::Malformed(message) => write!(f, "malformed bytecode: {}", message),
+        }
+    }
+}
+
+impl Error for LoadError {}
@@ -0,0 +1,311 @@
+//! The on-disk form of compiled bytecode, so that a script can be compiled once with
+//! `tarzan build` and run many times.
+//!
+//! A file is laid out as follows, with every integer big-endian:
+//!
+//! ```text
+//! magic        4 bytes   "MKC\0"
+//! version      u16       FORMAT_VERSION
+//! constants    u32 count, then each constant as a u8 tag followed by
+//!                          0: an i64 integer, or 1: a string
+//! globals      u32 count, then each name as a string
+//! functions    u32 count, then each function prototype
+//! main         the prototype of the top level of the program
+//! checksum     u32       CRC-32 of everything before it
+//! ```
+//!
+//! A string is a u32 length followed by that many bytes of UTF-8. A function prototype is its
+//! name (a u8 that is 1 if a string follows), its parameter names and its local names (each a u32
+//! count of strings), its free variables (a u32 count of entries, each the name, a u8 that is 0 for
+//! a local and 1 for a free variable of the creating function, and the u32 index there), its
+//! instructions (a u32 length followed by the bytes) and its debug line table: a u32 count of
+//! entries, each the u32 instruction offset followed by the u32 start, end, line and column of the
+//! span that the instructions from that offset were compiled from.
+//!
+//! The version comes right after the magic number and is checked before anything else, so that a
+//! file from another version is rejected with a clear error however its layout differs. Bump
+//! `FORMAT_VERSION` whenever the layout or the instruction set changes.
+
+use std::rc::Rc;
+use crate::compiler::code::{self, Opcode};
+use crate::compiler::error::LoadError;
+use crate::compiler::symbol_table::{Symbol, SymbolScope};
+use crate::compiler::{Bytecode, CompiledFunction, Constant};
+use crate::evaluator::builtins::BUILTINS;
+use crate::token::Span;
+
+pub const MAGIC: &[u8; 4] = b"MKC\0";
+pub const FORMAT_VERSION: u16 = 3;
+
+mod tag {
+    pub const INTEGER: u8 = 0;
+    pub const STRING: u8 = 1;
+    pub const LOCAL: u8 = 0;
+    pub const FREE: u8 = 1;
+}
+
+/// Whether `bytes` start like a bytecode file, as opposed to Monkey source.
+pub fn is_bytecode(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+/// Serializes `bytecode` in the current format version.
+pub fn write(bytecode: &Bytecode) -> Vec<u8> {
+    let mut bytes = MAGIC.to_vec();
+    bytes.extend_from_slice(&FORMAT_VERSION.to_be_bytes());
+
+    write_u32(&mut bytes, bytecode.constants.len());
+    for constant in bytecode.constants.iter() {
+        match constant {
+            Constant::Integer(value) => {
+                bytes.push(tag::INTEGER);
+                bytes.extend_from_slice(&value.to_be_bytes());
+            }
+            Constant::Str(value) => {
+                bytes.push(tag::STRING);
+                write_string(&mut bytes, value);
+            }
+        }
+    }
+    write_u32(&mut bytes, bytecode.globals.len());
+    for name in bytecode.globals.iter() {
+        write_string(&mut bytes, name);
+    }
+    write_u32(&mut bytes, bytecode.functions.len());
+    for function in bytecode.functions.iter() {
+        write_function(&mut bytes, function);
+    }
+    write_function(&mut bytes, &bytecode.main);
+
+    bytes.extend_from_slice(&checksum(&bytes).to_be_bytes());
+    return bytes;
+}
+
+/// Deserializes a bytecode file written by `write`, checking its version and checksum and that
+/// its instructions only refer to constants, globals, functions and builtins that exist.
+pub fn read(bytes: &[u8]) -> Result<Bytecode, LoadError> {
+    if !is_bytecode(bytes) {
+        return Err(LoadError::NotBytecode);
+    }
+    let mut reader = Reader { bytes, position: MAGIC.len() };
+    let version = reader.u16()?;
+    if version != FORMAT_VERSION {
+        return Err(LoadError::UnsupportedVersion { found: version, expected: FORMAT_VERSION });
+    }
+
+    let contents_length = bytes.len().checked_sub(4).ok_or(LoadError::Truncated)?;
+    if contents_length < reader.position {
+        return Err(LoadError::Truncated);
+    }
+    let (contents, stored) = bytes.split_at(contents_length);
+    if checksum(contents) != u32::from_be_bytes([stored[0], stored[1], stored[2], stored[3]]) {
+        return Err(LoadError::ChecksumMismatch);
+    }
+    reader.bytes = contents;
+
+    let mut constants = Vec::new();
+    for _ in 0..reader.u32()? {
+        constants.push(match reader.u8()? {
+            tag::INTEGER => Constant::Integer(i64::from_be_bytes(reader.array()?)),
+            tag::STRING => Constant::Str(reader.string()?),
+            tag => return Err(LoadError::Malformed(format!("unknown constant tag {}", tag))),
+        });
+    }
+    let mut globals = Vec::new();
+    for _ in 0..reader.u32()? {
+        globals.push(reader.string()?);
+    }
+    let mut functions = Vec::new();
+    for _ in 0..reader.u32()? {
+        functions.push(Rc::new(reader.function()?));
+    }
+    let main = Rc::new(reader.function()?);
+    if reader.position != contents.len() {
+        return Err(LoadError::Malformed("unexpected data after the program".to_string()));
+    }
+
+    let bytecode = Bytecode { constants, functions, main, globals };
+    let main = &bytecode.main;
+    if !main.parameters.is_empty() || !main.locals.is_empty() || !main.free.is_empty() {
+        return Err(LoadError::Malformed("the main function has parameters, locals or free variables".to_string()));
+    }
+    validate(&bytecode, main)?;
+    for function in bytecode.functions.iter() {
+        validate(&bytecode, function)?;
+    }
+    return Ok(bytecode);
+}
+
+fn write_function(bytes: &mut Vec<u8>, function: &CompiledFunction) {
+    match &function.name {
+        Some(name) => {
+            bytes.push(1);
+            write_string(bytes, name);
+        }
+        None => bytes.push(0),
+    }
+    write_u32(bytes, function.parameters.len());
+    for parameter in function.parameters.iter() {
+        write_string(bytes, parameter);
+    }
+    write_u32(bytes, function.locals.len());
+    for local in function.locals.iter() {
+        write_string(bytes, local);
+    }
+    write_u32(bytes, function.free.len());
+    for symbol in function.free.iter() {
+        write_string(bytes, &symbol.name);
+        bytes.push(match symbol.scope {
+            SymbolScope::Local => tag::LOCAL,
+            _ => tag::FREE,
+        });
+        write_u32(bytes, symbol.index);
+    }
+    write_u32(bytes, function.instructions.len());
+    bytes.extend_from_slice(&function.instructions);
+
+    write_u32(bytes, function.spans.len());
+    for (offset, span) in function.spans.iter() {
+        for value in [*offset, span.start, span.end, span.line, span.column] {
+            write_u32(bytes, value);
+        }
+    }
+}
+
+fn write_string(bytes: &mut Vec<u8>, value: &str) {
+    write_u32(bytes, value.len());
+    bytes.extend_from_slice(value.as_bytes());
+}
+
+fn write_u32(bytes: &mut Vec<u8>, value: usize) {
+    bytes.extend_from_slice(&(value as u32).to_be_bytes());
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl Reader<'_> {
+    fn take(&mut self, length: usize) -> Result<&[u8], LoadError> {
+        let end = self.position.checked_add(length).ok_or(LoadError::Truncated)?;
+        let taken = self.bytes.get(self.position..end).ok_or(LoadError::Truncated)?;
+        self.position = end;
+        return Ok(taken);
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], LoadError> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+        return Ok(array);
+    }
+
+    fn u8(&mut self) -> Result<u8, LoadError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, LoadError> {
+        Ok(u16::from_be_bytes(self.array()?))
+    }
+
+    fn u32(&mut self) -> Result<usize, LoadError> {
+        Ok(u32::from_be_bytes(self.array()?) as usize)
+    }
+
+    fn string(&mut self) -> Result<String, LoadError> {
+        let length = self.u32()?;
+        let bytes = self.take(length)?.to_vec();
+        String::from_utf8(bytes).map_err(|_| LoadError::Malformed("string is not valid UTF-8".to_string()))
+    }
+
+    fn function(&mut self) -> Result<CompiledFunction, LoadError> {
+        let name = match self.u8()? {
+            0 => None,
+            _ => Some(self.string()?),
+        };
+        let mut parameters = Vec::new();
+        for _ in 0..self.u32()? {
+            parameters.push(self.string()?);
+        }
+        let mut locals = Vec::new();
+        for _ in 0..self.u32()? {
+            locals.push(self.string()?);
+        }
+        let mut free = Vec::new();
+        for _ in 0..self.u32()? {
+            let name = self.string()?;
+            let scope = match self.u8()? {
+                tag::LOCAL => SymbolScope::Local,
+                tag::FREE => SymbolScope::Free,
+                tag => return Err(LoadError::Malformed(format!("unknown free variable tag {}", tag))),
+            };
+            free.push(Symbol { name, scope, index: self.u32()? });
+        }
+        let length = self.u32()?;
+        let instructions = self.take(length)?.to_vec();
+
+        let mut spans = Vec::new();
+        for _ in 0..self.u32()? {
+            let offset = self.u32()?;
+            let span = Span { start: self.u32()?, end: self.u32()?, line: self.u32()?, column: self.u32()? };
+            spans.push((offset, span));
+        }
+        if !spans.windows(2).all(|pair| pair[0].0 < pair[1].0) {
+            return Err(LoadError::Malformed("line table is not in order of offset".to_string()));
+        }
+
+        return Ok(CompiledFunction { name, parameters, locals, free, instructions, spans });
+    }
+}
+
+/// Checks that every instruction of `function` decodes and that its operands index into the tables
+/// of `bytecode`. Together with the checksum this catches files that are damaged or were not written
+/// by `write`. It does not prove that the instructions keep the stack balanced, which the VM checks
+/// as it runs them instead.
+fn validate(bytecode: &Bytecode, function: &CompiledFunction) -> Result<(), LoadError> {
+    let instructions = &function.instructions;
+    if !function.locals.starts_with(&function.parameters) {
+        return Err(LoadError::Malformed("a function's locals do not start with its parameters".to_string()));
+    }
+
+    let mut offset = 0;
+    while offset < instructions.len() {
+        let malformed = |what: &str| LoadError::Malformed(format!("{} at offset {}", what, offset));
+        let opcode = Opcode::from_byte(instructions[offset]).ok_or_else(|| malformed("unknown opcode"))?;
+        let (operands, width) =
+            code::read_operands(opcode, &instructions[offset + 1..]).ok_or_else(|| malformed("truncated instruction"))?;
+
+        let in_range = match opcode {
+            Opcode::Constant => operands[0] < bytecode.constants.len(),
+            Opcode::GetGlobal | Opcode::SetGlobal => operands[0] < bytecode.globals.len(),
+            Opcode::GetLocal | Opcode::SetLocal => operands[0] < function.locals.len(),
+            Opcode::GetFree => operands[0] < function.free.len(),
+            Opcode::GetBuiltin => operands[0] < BUILTINS.len(),
+            Opcode::Closure => bytecode.functions.get(operands[0]).is_some_and(|prototype| {
+                prototype.free.iter().all(|symbol| match symbol.scope {
+                    SymbolScope::Local => symbol.index < function.locals.len(),
+                    _ => symbol.index < function.free.len(),
+                })
+            }),
+            Opcode::Jump | Opcode::JumpNotTruthy => operands[0] < instructions.len(),
+            _ => true,
+        };
+        if !in_range {
+            return Err(malformed(&format!("operand out of range for {}", opcode.name())));
+        }
+        offset += 1 + width;
+    }
+    return Ok(());
+}
+
+/// The CRC-32 (IEEE) of `bytes`, as stored at the end of a file.
+pub fn checksum(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in bytes {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xEDB8_8320 & (crc & 1).wrapping_neg());
+        }
+    }
+    return !crc;
+}
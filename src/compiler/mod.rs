@@ -0,0 +1,413 @@
+pub mod code;
+pub mod disassembler;
+pub mod error;
+pub mod file;
+pub mod symbol_table;
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::rc::Rc;
+use crate::ast::{scope, BlockStatement, Expression, LetStatement, Program, Statement};
+use crate::compiler::code::Opcode;
+use crate::compiler::error::CompileError;
+use crate::compiler::symbol_table::{Symbol, SymbolScope, SymbolTable};
+use crate::evaluator::builtins::BUILTINS;
+use crate::token::{Span, Token};
+
+const MAX_U8_OPERAND: usize = u8::MAX as usize;
+const MAX_U16_OPERAND: usize = u16::MAX as usize;
+
+/// A value known at compile time, stored once in the constant pool and loaded with
+/// `Opcode::Constant`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Constant {
+    Integer(i64),
+    Str(String),
+}
+
+impl Display for Constant {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Constant::Integer(value) => write!(f, "{}", value),
+            Constant::Str(value) => write!(f, "{:?}", value),
+        }
+    }
+}
+
+/// The compiled body of a function literal, or of the program itself, from which the VM creates
+/// closures.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompiledFunction {
+    /// The name the function was bound to with `let`, if any.
+    pub name: Option<String>,
+    pub parameters: Vec<String>,
+    /// The names of the function's local variables by slot, parameters first.
+    pub locals: Vec<String>,
+    /// The variables of the function that creates the closure which the closure shares, in the
+    /// order `Opcode::GetFree` numbers them. Each is a local or free symbol of that function.
+    pub free: Vec<Symbol>,
+    pub instructions: Vec<u8>,
+    /// The span of the source each instruction was compiled from, keyed by the instruction's
+    /// offset, in increasing order of offset.
+    pub spans: Vec<(usize, Span)>,
+}
+
+impl CompiledFunction {
+    /// The span of the source the instruction at `offset` was compiled from.
+    pub fn span_at(&self, offset: usize) -> Option<Span> {
+        match self.spans.binary_search_by_key(&offset, |(start, _)| *start) {
+            Ok(index) => Some(self.spans[index].1),
+            Err(0) => None,
+            Err(index) => Some(self.spans[index - 1].1),
+        }
+    }
+}
+
+/// A compiled program, ready for the VM.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bytecode {
+    pub constants: Vec<Constant>,
+    /// The function literals of the program, indexed by the first operand of `Opcode::Closure`.
+    pub functions: Vec<Rc<CompiledFunction>>,
+    /// The top level of the program, run as a function without parameters whose return value is
+    /// the value of the program.
+    pub main: Rc<CompiledFunction>,
+    /// The names of the global variables, by index.
+    pub globals: Vec<String>,
+}
+
+#[derive(Default)]
+struct CompilationScope {
+    instructions: Vec<u8>,
+    spans: Vec<(usize, Span)>,
+}
+
+/// Lowers a syntax tree to bytecode. Each function literal is compiled in a scope of its own and
+/// becomes a `CompiledFunction`. Names are resolved at compile time to global, local, captured or
+/// builtin slots, and blocks leave the value on the stack that the evaluator would return for them.
+pub struct Compiler {
+    constants: Vec<Constant>,
+    constant_indices: HashMap<Constant, usize>,
+    functions: Vec<Rc<CompiledFunction>>,
+    symbol_table: SymbolTable,
+    scopes: Vec<CompilationScope>,
+}
+
+pub fn new() -> Compiler {
+    let mut symbol_table = symbol_table::new();
+    for (index, builtin) in BUILTINS.iter().enumerate() {
+        symbol_table.define_builtin(index, builtin.name());
+    }
+
+    Compiler {
+        constants: Vec::new(),
+        constant_indices: HashMap::new(),
+        functions: Vec::new(),
+        symbol_table,
+        scopes: vec![CompilationScope::default()],
+    }
+}
+
+pub fn compile(program: &Program) -> Result<Bytecode, CompileError> {
+    new().compile(program)
+}
+
+impl Compiler {
+    pub fn compile(mut self, program: &Program) -> Result<Bytecode, CompileError> {
+        let span = match (program.statements.first(), program.statements.last()) {
+            (Some(first), Some(last)) => first.span().to(last.span()),
+            _ => Span::default(),
+        };
+        for name in scope::let_names(&program.statements) {
+            self.symbol_table.define(&name);
+        }
+        self.compile_block_value(&program.statements, span)?;
+        self.emit(Opcode::Return, &[], span)?;
+
+        let scope = self.scopes.pop().unwrap_or_default();
+        let main = CompiledFunction {
+            name: None,
+            parameters: Vec::new(),
+            locals: Vec::new(),
+            free: Vec::new(),
+            instructions: scope.instructions,
+            spans: scope.spans,
+        };
+        if self.symbol_table.names.len() > MAX_U16_OPERAND + 1 {
+            return Err(CompileError::LimitExceeded { what: "global variables", limit: MAX_U16_OPERAND + 1, span });
+        }
+
+        return Ok(Bytecode {
+            constants: self.constants,
+            functions: self.functions,
+            main: Rc::new(main),
+            globals: self.symbol_table.names,
+        });
+    }
+
+    /// Compiles `statements` so that they leave one value on the stack: the value of the last
+    /// statement if it is an expression, and otherwise null.
+    fn compile_block_value(&mut self, statements: &[Statement], span: Span) -> Result<(), CompileError> {
+        if statements.is_empty() {
+            self.emit(Opcode::Null, &[], span)?;
+            return Ok(());
+        }
+
+        for (index, statement) in statements.iter().enumerate() {
+            let is_last = index + 1 == statements.len();
+            match statement {
+                Statement::Let(let_statement) => {
+                    self.compile_let_statement(let_statement)?;
+                    if is_last {
+                        self.emit(Opcode::Null, &[], let_statement.span)?;
+                    }
+                }
+                Statement::Return(return_statement) => {
+                    self.compile_expression(&return_statement.value)?;
+                    self.emit(Opcode::Return, &[], return_statement.span)?;
+                }
+                Statement::Expression(expression) => {
+                    self.compile_expression(expression)?;
+                    if !is_last {
+                        self.emit(Opcode::Pop, &[], expression.span())?;
+                    }
+                }
+                Statement::Error(span) => return Err(CompileError::SyntaxError { span: *span }),
+            }
+        }
+        return Ok(());
+    }
+
+    fn compile_block(&mut self, block: &Option<BlockStatement>, span: Span) -> Result<(), CompileError> {
+        match block {
+            Some(block) => self.compile_block_value(&block.statements, block.span),
+            None => self.compile_block_value(&[], span),
+        }
+    }
+
+    fn compile_let_statement(&mut self, let_statement: &LetStatement) -> Result<(), CompileError> {
+        let name = &let_statement.identifier_name;
+        match &let_statement.value {
+            Expression::Function { parameters, body, span, .. } => {
+                self.compile_function(Some(name), parameters, body, *span)?
+            }
+            value => self.compile_expression(value)?,
+        }
+
+        let symbol = self.symbol_table.define(name);
+        match symbol.scope {
+            SymbolScope::Global => self.emit(Opcode::SetGlobal, &[symbol.index], let_statement.span)?,
+            _ => self.emit(Opcode::SetLocal, &[symbol.index], let_statement.span)?,
+        };
+        return Ok(());
+    }
+
+    fn compile_expression(&mut self, expression: &Expression) -> Result<(), CompileError> {
+        match expression {
+            Expression::Identifier { token: Token::Identifier { literal }, span } => {
+                let symbol = self.symbol_table.resolve(literal);
+                self.load_symbol(&symbol, *span)?;
+            }
+            Expression::Identifier { span, .. } => return Err(CompileError::SyntaxError { span: *span }),
+            Expression::Boolean { value, span, .. } => {
+                self.emit(if *value { Opcode::True } else { Opcode::False }, &[], *span)?;
+            }
+            Expression::IntegerLiteral { value, span, .. } => {
+                let index = self.add_constant(Constant::Integer(*value), *span)?;
+                self.emit(Opcode::Constant, &[index], *span)?;
+            }
+            Expression::StringLiteral { value, span, .. } => {
+                let index = self.add_constant(Constant::Str(value.clone()), *span)?;
+                self.emit(Opcode::Constant, &[index], *span)?;
+            }
+            Expression::PrefixExpression { operator, right, span } => {
+                let opcode = match operator.as_str() {
+                    "-" => Opcode::Negate,
+                    "!" => Opcode::Not,
+                    "+" => Opcode::Positive,
+                    _ => return Err(CompileError::UnknownOperator { operator: operator.clone(), span: *span }),
+                };
+                self.compile_expression(right)?;
+                self.emit(opcode, &[], *span)?;
+            }
+            Expression::InfixExpression { operator, left, right, span } => {
+                let opcode = match operator.as_str() {
+                    "+" => Opcode::Add,
+                    "-" => Opcode::Subtract,
+                    "*" => Opcode::Multiply,
+                    "/" => Opcode::Divide,
+                    "==" => Opcode::Equal,
+                    "!=" => Opcode::NotEqual,
+                    "<" => Opcode::LessThan,
+                    ">" => Opcode::GreaterThan,
+                    _ => return Err(CompileError::UnknownOperator { operator: operator.clone(), span: *span }),
+                };
+                self.compile_expression(left)?;
+                self.compile_expression(right)?;
+                self.emit(opcode, &[], *span)?;
+            }
+            Expression::IfExpression { condition, consequence, alternative, span, .. } => {
+                self.compile_expression(condition)?;
+                let jump_not_truthy = self.emit(Opcode::JumpNotTruthy, &[0], *span)?;
+
+                self.compile_block(consequence, *span)?;
+                let jump = self.emit(Opcode::Jump, &[0], *span)?;
+
+                let alternative_start = self.current_offset();
+                self.change_operand(jump_not_truthy, alternative_start, *span)?;
+                self.compile_block(alternative, *span)?;
+
+                let end = self.current_offset();
+                self.change_operand(jump, end, *span)?;
+            }
+            Expression::Function { parameters, body, span, .. } => self.compile_function(None, parameters, body, *span)?,
+            Expression::Call { function, arguments, span } => {
+                if arguments.len() > MAX_U8_OPERAND {
+                    return Err(CompileError::LimitExceeded { what: "arguments", limit: MAX_U8_OPERAND, span: *span });
+                }
+                self.compile_expression(function)?;
+                for argument in arguments.iter() {
+                    self.compile_expression(argument)?;
+                }
+                self.emit(Opcode::Call, &[arguments.len()], *span)?;
+            }
+            Expression::ArrayLiteral { elements, span } => {
+                if elements.len() > MAX_U16_OPERAND {
+                    return Err(CompileError::LimitExceeded { what: "array elements", limit: MAX_U16_OPERAND, span: *span });
+                }
+                for element in elements.iter() {
+                    self.compile_expression(element)?;
+                }
+                self.emit(Opcode::Array, &[elements.len()], *span)?;
+            }
+            Expression::Index { left, index, span } => {
+                self.compile_expression(left)?;
+                self.compile_expression(index)?;
+                self.emit(Opcode::Index, &[], *span)?;
+            }
+            Expression::HashLiteral { pairs, span } => {
+                if pairs.len() * 2 > MAX_U16_OPERAND {
+                    return Err(CompileError::LimitExceeded { what: "hash pairs", limit: MAX_U16_OPERAND / 2, span: *span });
+                }
+                for (key, value) in pairs.iter() {
+                    self.compile_expression(key)?;
+                    self.emit(Opcode::CheckHashKey, &[], *span)?;
+                    self.compile_expression(value)?;
+                }
+                self.emit(Opcode::Hash, &[pairs.len() * 2], *span)?;
+            }
+            Expression::Error { span } => return Err(CompileError::SyntaxError { span: *span }),
+        }
+        return Ok(());
+    }
+
+    /// Compiles a function literal into a prototype of its own and emits the instruction that
+    /// turns it into a closure sharing the variables it captures. `name` is the name it is bound
+    /// to with `let`, which only serves to describe it.
+    fn compile_function(&mut self, name: Option<&str>, parameters: &[Expression], body: &BlockStatement, span: Span) -> Result<(), CompileError> {
+        self.scopes.push(CompilationScope::default());
+        self.symbol_table = symbol_table::new_enclosed(std::mem::take(&mut self.symbol_table));
+
+        let mut parameter_names = Vec::with_capacity(parameters.len());
+        for parameter in parameters.iter() {
+            match parameter {
+                Expression::Identifier { token: Token::Identifier { literal }, .. } => {
+                    self.symbol_table.define(literal);
+                    parameter_names.push(literal.clone());
+                }
+                _ => return Err(CompileError::SyntaxError { span: parameter.span() }),
+            }
+        }
+        for name in scope::let_names(&body.statements) {
+            self.symbol_table.define(&name);
+        }
+
+        self.compile_block_value(&body.statements, body.span)?;
+        self.emit(Opcode::Return, &[], body.span)?;
+
+        let scope = self.scopes.pop().unwrap_or_default();
+        let function_table = std::mem::take(&mut self.symbol_table);
+        self.symbol_table = *function_table.outer.unwrap_or_default();
+
+        if function_table.names.len() > MAX_U8_OPERAND + 1 {
+            return Err(CompileError::LimitExceeded { what: "local variables", limit: MAX_U8_OPERAND + 1, span });
+        }
+        if function_table.free_symbols.len() > MAX_U8_OPERAND {
+            return Err(CompileError::LimitExceeded { what: "captured variables", limit: MAX_U8_OPERAND, span });
+        }
+        if self.functions.len() > MAX_U16_OPERAND {
+            return Err(CompileError::LimitExceeded { what: "functions", limit: MAX_U16_OPERAND + 1, span });
+        }
+
+        let index = self.functions.len();
+        self.functions.push(Rc::new(CompiledFunction {
+            name: name.map(str::to_string),
+            parameters: parameter_names,
+            locals: function_table.names,
+            free: function_table.free_symbols,
+            instructions: scope.instructions,
+            spans: scope.spans,
+        }));
+        self.emit(Opcode::Closure, &[index], span)?;
+        return Ok(());
+    }
+
+    fn load_symbol(&mut self, symbol: &Symbol, span: Span) -> Result<usize, CompileError> {
+        match symbol.scope {
+            SymbolScope::Global => self.emit(Opcode::GetGlobal, &[symbol.index], span),
+            SymbolScope::Local => self.emit(Opcode::GetLocal, &[symbol.index], span),
+            SymbolScope::Free => self.emit(Opcode::GetFree, &[symbol.index], span),
+            SymbolScope::Builtin => self.emit(Opcode::GetBuiltin, &[symbol.index], span),
+        }
+    }
+
+    fn add_constant(&mut self, constant: Constant, span: Span) -> Result<usize, CompileError> {
+        if let Some(index) = self.constant_indices.get(&constant) {
+            return Ok(*index);
+        }
+
+        let index = self.constants.len();
+        if index > MAX_U16_OPERAND {
+            return Err(CompileError::LimitExceeded { what: "constants", limit: MAX_U16_OPERAND + 1, span });
+        }
+        self.constants.push(constant.clone());
+        self.constant_indices.insert(constant, index);
+        return Ok(index);
+    }
+
+    /// Appends an instruction to the function being compiled and returns its offset.
+    fn emit(&mut self, opcode: Opcode, operands: &[usize], span: Span) -> Result<usize, CompileError> {
+        let instruction = code::make(opcode, operands);
+        let scope = self.current_scope();
+        let offset = scope.instructions.len();
+        if offset + instruction.len() > MAX_U16_OPERAND {
+            return Err(CompileError::LimitExceeded { what: "bytes of instructions in one function", limit: MAX_U16_OPERAND, span });
+        }
+
+        scope.instructions.extend(instruction);
+        if scope.spans.last().map(|(_, last)| *last) != Some(span) {
+            scope.spans.push((offset, span));
+        }
+        return Ok(offset);
+    }
+
+    /// Rewrites the operand of the jump at `offset` once its target is known.
+    fn change_operand(&mut self, offset: usize, operand: usize, span: Span) -> Result<(), CompileError> {
+        if operand > MAX_U16_OPERAND {
+            return Err(CompileError::LimitExceeded { what: "bytes of instructions in one function", limit: MAX_U16_OPERAND, span });
+        }
+        let scope = self.current_scope();
+        let opcode = Opcode::from_byte(scope.instructions[offset]).unwrap_or(Opcode::Jump);
+        let instruction = code::make(opcode, &[operand]);
+        scope.instructions[offset..offset + instruction.len()].copy_from_slice(&instruction);
+        return Ok(());
+    }
+
+    fn current_offset(&mut self) -> usize {
+        self.current_scope().instructions.len()
+    }
+
+    fn current_scope(&mut self) -> &mut CompilationScope {
+        self.scopes.last_mut().expect("the compiler always has the program's scope")
+    }
+}
@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolScope {
+    /// Bound at the top level of the program, stored in the VM's globals by index.
+    Global,
+    /// Bound inside a function, stored in the function's frame by index.
+    Local,
+    /// Bound in an enclosing function and shared with the closure that refers to it.
+    Free,
+    /// A function provided by the interpreter, by its index in `builtins::BUILTINS`.
+    Builtin,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbol {
+    pub name: String,
+    pub scope: SymbolScope,
+    pub index: usize,
+}
+
+/// The names visible while compiling one function, and the table of the function around it.
+#[derive(Debug, Default)]
+pub struct SymbolTable {
+    pub outer: Option<Box<SymbolTable>>,
+    store: HashMap<String, Symbol>,
+    /// The names of the definitions in this table, by index.
+    pub names: Vec<String>,
+    /// The symbols of the enclosing functions this function captures, in the order the closure
+    /// stores them.
+    pub free_symbols: Vec<Symbol>,
+}
+
+impl SymbolTable {
+    /// Binds `name` in this table. Binding a name again, as `let x = x + 1;` does, reuses its slot.
+    pub fn define(&mut self, name: &str) -> Symbol {
+        if let Some(symbol) = self.store.get(name) {
+            if symbol.scope == SymbolScope::Global || symbol.scope == SymbolScope::Local {
+                return symbol.clone();
+            }
+        }
+
+        let scope = match self.outer {
+            Some(_) => SymbolScope::Local,
+            None => SymbolScope::Global,
+        };
+        let symbol = Symbol { name: name.to_string(), scope, index: self.names.len() };
+        self.names.push(name.to_string());
+        self.store.insert(name.to_string(), symbol.clone());
+        return symbol;
+    }
+
+    pub fn define_builtin(&mut self, index: usize, name: &str) -> Symbol {
+        let symbol = Symbol { name: name.to_string(), scope: SymbolScope::Builtin, index };
+        self.store.insert(name.to_string(), symbol.clone());
+        return symbol;
+    }
+
+    /// Finds the binding `name` refers to. A local of an enclosing function becomes a free symbol
+    /// of every function in between. A name bound nowhere is declared as a global, which raises
+    /// an error at runtime if it is still unset when read, so that functions may refer to globals
+    /// that are defined after them.
+    pub fn resolve(&mut self, name: &str) -> Symbol {
+        if let Some(symbol) = self.store.get(name) {
+            return symbol.clone();
+        }
+
+        let outer = match self.outer.as_mut() {
+            Some(outer) => outer,
+            None => return self.define(name),
+        };
+        let symbol = outer.resolve(name);
+        if symbol.scope == SymbolScope::Global || symbol.scope == SymbolScope::Builtin {
+            return symbol;
+        }
+        return self.define_free(symbol);
+    }
+
+    fn define_free(&mut self, original: Symbol) -> Symbol {
+        let symbol = Symbol { name: original.name.clone(), scope: SymbolScope::Free, index: self.free_symbols.len() };
+        self.free_symbols.push(original);
+        self.store.insert(symbol.name.clone(), symbol.clone());
+        return symbol;
+    }
+}
+
+pub fn new() -> SymbolTable {
+    SymbolTable::default()
+}
+
+pub fn new_enclosed(outer: SymbolTable) -> SymbolTable {
+    SymbolTable { outer: Some(Box::new(outer)), ..SymbolTable::default() }
+}
@@ -0,0 +1,196 @@
+use std::fmt::Write;
+use crate::compiler::error::CompileError;
+use crate::evaluator::error::RuntimeError;
+use crate::lexer::error::LexError;
+use crate::optimizer::Warning;
+use crate::parser::error::ParseError;
+use crate::token::{Span, Token};
+
+pub mod code {
+    pub const END_OF_INPUT: &str = "E0001";
+    pub const UNTERMINATED_STRING: &str = "E0002";
+    pub const UNTERMINATED_COMMENT: &str = "E0003";
+    pub const INVALID_ESCAPE: &str = "E0004";
+    pub const UNEXPECTED_TOKEN: &str = "E0101";
+    pub const EXPECTED_EXPRESSION: &str = "E0102";
+    pub const INTEGER_OVERFLOW: &str = "E0103";
+    pub const UNCLOSED_DELIMITER: &str = "E0104";
+    pub const RUNTIME: &str = "E0201";
+    pub const COMPILE: &str = "E0301";
+    pub const DIVISION_BY_ZERO: &str = "W0001";
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    Error,
+    /// Something that is likely a mistake but does not stop the program from being run.
+    Warning,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Style {
+    Plain,
+    Ansi,
+}
+
+const RED: &str = "\x1b[1;31m";
+const BLUE: &str = "\x1b[1;34m";
+const YELLOW: &str = "\x1b[1;33m";
+const CYAN: &str = "\x1b[1;36m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+/// An error ready to be shown to a user: what went wrong, where, and what might fix it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: &'static str,
+    pub message: String,
+    pub span: Option<Span>,
+    pub help: Vec<String>,
+}
+
+impl From<&LexError> for Diagnostic {
+    fn from(error: &LexError) -> Self {
+        let (code, help) = match error {
+            LexError::EndOfInput => (code::END_OF_INPUT, vec![]),
+            LexError::UnterminatedString { .. } => {
+                (code::UNTERMINATED_STRING, vec!["add a closing `\"` to end the string".to_string()])
+            }
+            LexError::UnterminatedComment { .. } => {
+                (code::UNTERMINATED_COMMENT, vec!["add a closing `*/`; nested `/*` need their own".to_string()])
+            }
+            LexError::InvalidEscape { .. } => (
+                code::INVALID_ESCAPE,
+                vec!["valid escapes are `\\n`, `\\t`, `\\r`, `\\0`, `\\\"`, `\\\\` and `\\u{...}`".to_string()],
+            ),
+        };
+        Diagnostic { severity: Severity::Error, code, message: error.message(), span: error.span(), help }
+    }
+}
+
+impl From<&ParseError> for Diagnostic {
+    fn from(error: &ParseError) -> Self {
+        let (code, help) = match error {
+            ParseError::UnexpectedToken { found: Token::Eof, .. } => {
+                (code::UNEXPECTED_TOKEN, vec!["the input ended before this was complete".to_string()])
+            }
+            ParseError::UnexpectedToken { .. } => (code::UNEXPECTED_TOKEN, vec![]),
+            ParseError::NoPrefixParser { token: Token::Eof, .. } => {
+                (code::EXPECTED_EXPRESSION, vec!["expected an expression before the end of input".to_string()])
+            }
+            ParseError::NoPrefixParser { token, .. } => {
+                (code::EXPECTED_EXPRESSION, vec![format!("`{}` cannot start an expression", token)])
+            }
+            ParseError::IntegerOverflow { .. } => (
+                code::INTEGER_OVERFLOW,
+                vec![format!("integers must be between {} and {}", i64::MIN, i64::MAX)],
+            ),
+            ParseError::UnclosedDelimiter { delimiter, .. } => (
+                code::UNCLOSED_DELIMITER,
+                vec![format!("expected `{}` to close `{}` opened here", closing_delimiter(delimiter), delimiter)],
+            ),
+            ParseError::Lexer { error, .. } => return Diagnostic::from(error),
+        };
+        Diagnostic { severity: Severity::Error, code, message: error.message(), span: Some(error.span()), help }
+    }
+}
+
+impl From<&RuntimeError> for Diagnostic {
+    fn from(error: &RuntimeError) -> Self {
+        Diagnostic {
+            severity: Severity::Error,
+            code: code::RUNTIME,
+            message: error.message.clone(),
+            span: error.span,
+            help: vec![],
+        }
+    }
+}
+
+impl From<&CompileError> for Diagnostic {
+    fn from(error: &CompileError) -> Self {
+        Diagnostic {
+            severity: Severity::Error,
+            code: code::COMPILE,
+            message: error.message(),
+            span: Some(error.span()),
+            help: vec![],
+        }
+    }
+}
+
+impl From<&Warning> for Diagnostic {
+    fn from(warning: &Warning) -> Self {
+        let (code, help) = match warning {
+            Warning::DivisionByZero { .. } => (code::DIVISION_BY_ZERO, vec!["this fails at runtime if it is reached".to_string()]),
+        };
+        Diagnostic { severity: Severity::Warning, code, message: warning.message(), span: Some(warning.span()), help }
+    }
+}
+
+impl Diagnostic {
+    /// Renders the diagnostic in the style of a compiler error, quoting the line of `source` that
+    /// `span` starts on and underlining the span on it:
+    ///
+    /// ```text
+    /// error[E0101]: expected `=`, found `5`
+    ///  --> script.mk:1:7
+    ///   |
+    /// 1 | let x 5;
+    ///   |       ^
+    /// ```
+    pub fn render(&self, file_name: &str, source: &str, style: Style) -> String {
+        let paint = |color: &str, text: &str| match style {
+            Style::Plain => text.to_string(),
+            Style::Ansi => format!("{}{}{}", color, text, RESET),
+        };
+
+        let mut output = String::new();
+        let (label, color) = match self.severity {
+            Severity::Error => ("error", RED),
+            Severity::Warning => ("warning", YELLOW),
+        };
+        let _ = writeln!(output, "{}{}", paint(color, &format!("{}[{}]", label, self.code)), paint(BOLD, &format!(": {}", self.message)));
+
+        let span = match self.span {
+            Some(span) => span,
+            None => {
+                let _ = writeln!(output, "{} {}", paint(BLUE, "-->"), file_name);
+                for help in self.help.iter() {
+                    let _ = writeln!(output, "{} {}", paint(CYAN, "= help:"), help);
+                }
+                return output;
+            }
+        };
+
+        let line_number = span.line.to_string();
+        let gutter = " ".repeat(line_number.len());
+        let line = source.lines().nth(span.line.saturating_sub(1)).unwrap_or("");
+
+        let mut underline_offset = String::new();
+        for character in line.chars().take(span.column.saturating_sub(1)) {
+            underline_offset.push(if character == '\t' { '\t' } else { ' ' });
+        }
+        let remaining_on_line = line.chars().count().saturating_sub(span.column.saturating_sub(1));
+        let span_length = source.get(span.start..span.end).map_or(0, |text| text.chars().count());
+        let underline = "^".repeat(span_length.min(remaining_on_line).max(1));
+
+        let _ = writeln!(output, "{}{} {}:{}:{}", gutter, paint(BLUE, "-->"), file_name, span.line, span.column);
+        let _ = writeln!(output, "{} {}", gutter, paint(BLUE, "|"));
+        let _ = writeln!(output, "{} {}", paint(BLUE, &format!("{} |", line_number)), line);
+        let _ = writeln!(output, "{} {} {}{}", gutter, paint(BLUE, "|"), underline_offset, paint(color, &underline));
+        for help in self.help.iter() {
+            let _ = writeln!(output, "{} {} {}", gutter, paint(CYAN, "= help:"), help);
+        }
+        return output;
+    }
+}
+
+fn closing_delimiter(delimiter: &Token) -> Token {
+    match delimiter {
+        Token::LeftParenthesis => Token::RightParenthesis,
+        Token::LeftSquare => Token::RightSquare,
+        _ => Token::RightBracket,
+    }
+}
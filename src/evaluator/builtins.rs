@@ -0,0 +1,86 @@
+use std::io::Write;
+use crate::evaluator::object::{type_name, Object};
+
+/// A function provided by the interpreter rather than written in Monkey. A name resolves to a
+/// builtin only when no variable of that name is in scope.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Builtin {
+    Len,
+    First,
+    Last,
+    Rest,
+    Push,
+    Puts,
+}
+
+/// Every builtin, in the order the compiler numbers them.
+pub const BUILTINS: &[Builtin] = &[
+    Builtin::Len,
+    Builtin::First,
+    Builtin::Last,
+    Builtin::Rest,
+    Builtin::Push,
+    Builtin::Puts,
+];
+
+impl Builtin {
+    pub fn lookup(name: &str) -> Option<Builtin> {
+        BUILTINS.iter().find(|builtin| builtin.name() == name).copied()
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Builtin::Len => "len",
+            Builtin::First => "first",
+            Builtin::Last => "last",
+            Builtin::Rest => "rest",
+            Builtin::Push => "push",
+            Builtin::Puts => "puts",
+        }
+    }
+
+    /// Applies the builtin to `arguments`. `puts` writes to `output`.
+    pub fn call(&self, arguments: Vec<Object>, output: &mut dyn Write) -> Object {
+        let expected = match self {
+            Builtin::Puts => arguments.len(),
+            Builtin::Push => 2,
+            _ => 1,
+        };
+        if arguments.len() != expected {
+            return Object::error(format!(
+                "wrong number of arguments to `{}`: expected {}, got {}", self.name(), expected, arguments.len()
+            ));
+        }
+
+        match (self, arguments.as_slice()) {
+            (Builtin::Len, [Object::Str(value)]) => Object::Integer(value.chars().count() as i64),
+            (Builtin::Len, [Object::Array(elements)]) => Object::Integer(elements.len() as i64),
+            (Builtin::Len, [argument]) => {
+                Object::error(format!("argument to `len` not supported, got {}", argument.type_name()))
+            }
+            (Builtin::First, [Object::Array(elements)]) => elements.first().cloned().unwrap_or(Object::Null),
+            (Builtin::Last, [Object::Array(elements)]) => elements.last().cloned().unwrap_or(Object::Null),
+            (Builtin::Rest, [Object::Array(elements)]) => match elements.is_empty() {
+                true => Object::Null,
+                false => Object::Array(elements[1..].to_vec()),
+            },
+            (Builtin::Push, [Object::Array(elements), element]) => {
+                let mut elements = elements.clone();
+                elements.push(element.clone());
+                Object::Array(elements)
+            }
+            (Builtin::Puts, arguments) => {
+                for argument in arguments.iter() {
+                    if let Err(error) = writeln!(output, "{}", argument) {
+                        return Object::error(format!("could not write output: {}", error));
+                    }
+                }
+                Object::Null
+            }
+            (_, [argument, ..]) => Object::error(format!(
+                "argument to `{}` must be {}, got {}", self.name(), type_name::ARRAY, argument.type_name()
+            )),
+            (_, []) => Object::Null,
+        }
+    }
+}
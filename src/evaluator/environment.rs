@@ -0,0 +1,114 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Debug, Formatter};
+use std::rc::Rc;
+use crate::ast::{BlockStatement, Expression};
+use crate::evaluator::object::Object;
+
+/// The bindings of one scope and the scope around it. An `Environment` is a shared handle: clones
+/// refer to the same scope, so a function value can keep the scope it was defined in alive and see
+/// bindings added to it later, such as its own name when it is bound with `let`.
+///
+/// A name can be declared before it is bound, so that it refers to this scope from the start even
+/// though its `let` has not run yet. Until then the name hides the bindings of outer scopes.
+///
+/// A function bound in the scope it was defined in is stored without that scope and gets it back
+/// when it is read, so that recursive and mutually recursive functions do not keep their scope
+/// alive forever.
+#[derive(Clone, Default)]
+pub struct Environment {
+    scope: Rc<RefCell<Scope>>,
+}
+
+#[derive(Default)]
+struct Scope {
+    store: HashMap<String, Binding>,
+    outer: Option<Environment>,
+}
+
+enum Binding {
+    Value(Object),
+    /// A name that is declared but not yet bound.
+    Unset,
+    /// A function whose environment is the scope that holds the binding.
+    OwnFunction { parameters: Vec<Expression>, body: BlockStatement },
+}
+
+impl Environment {
+    pub fn get(&self, name: &str) -> Option<Object> {
+        let scope = self.scope.borrow();
+        match scope.store.get(name) {
+            Some(binding) => self.value(binding),
+            None => scope.outer.as_ref()?.get(name),
+        }
+    }
+
+    pub fn set(&mut self, name: String, value: Object) {
+        let binding = match value {
+            Object::Function { parameters, body, environment } if environment == *self => {
+                Binding::OwnFunction { parameters, body }
+            }
+            value => Binding::Value(value),
+        };
+        self.scope.borrow_mut().store.insert(name, binding);
+    }
+
+    /// Declares `name` in this scope without binding it, unless this scope already has it.
+    pub fn declare(&mut self, name: String) {
+        self.scope.borrow_mut().store.entry(name).or_insert(Binding::Unset);
+    }
+
+    fn value(&self, binding: &Binding) -> Option<Object> {
+        match binding {
+            Binding::Value(value) => Some(value.clone()),
+            Binding::Unset => None,
+            Binding::OwnFunction { parameters, body } => Some(Object::Function {
+                parameters: parameters.clone(),
+                body: body.clone(),
+                environment: self.clone(),
+            }),
+        }
+    }
+
+    /// Every binding visible from this environment, sorted by name. Inner bindings shadow outer ones.
+    pub fn bindings(&self) -> BTreeMap<String, Object> {
+        let scope = self.scope.borrow();
+        let mut bindings = match &scope.outer {
+            Some(outer) => outer.bindings(),
+            None => BTreeMap::new(),
+        };
+        for (name, binding) in scope.store.iter() {
+            match self.value(binding) {
+                Some(value) => bindings.insert(name.clone(), value),
+                None => bindings.remove(name),
+            };
+        }
+        return bindings;
+    }
+}
+
+/// Two environments are equal when they are the same scope.
+impl PartialEq for Environment {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.scope, &other.scope)
+    }
+}
+
+/// Only names the bindings, since their values may be functions that refer back to this scope.
+impl Debug for Environment {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let scope = self.scope.borrow();
+        let mut names: Vec<&String> = scope.store.keys().collect();
+        names.sort();
+        f.debug_set().entries(names).finish()
+    }
+}
+
+pub fn new() -> Environment {
+    Environment::default()
+}
+
+pub fn new_enclosed(outer: Environment) -> Environment {
+    let scope = Scope { store: HashMap::new(), outer: Some(outer) };
+    Environment { scope: Rc::new(RefCell::new(scope)) }
+}
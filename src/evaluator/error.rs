@@ -0,0 +1,45 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use crate::token::Span;
+
+/// An error raised while evaluating a program. `span` is the expression whose evaluation raised it,
+/// filled in by the evaluator as the error leaves that expression.
+#[derive(Debug, Clone, Eq)]
+pub struct RuntimeError {
+    pub message: String,
+    pub span: Option<Span>,
+}
+
+impl RuntimeError {
+    pub fn at(self, span: Span) -> RuntimeError {
+        RuntimeError { span: Some(span), ..self }
+    }
+}
+
+/// Runtime errors are values in Monkey, and two of them are equal when their messages are,
+/// wherever they were raised.
+impl PartialEq for RuntimeError {
+    fn eq(&self, other: &Self) -> bool {
+        self.message == other.message
+    }
+}
+
+impl From<String> for RuntimeError {
+    fn from(message: String) -> Self {
+        RuntimeError { message, span: None }
+    }
+}
+
+impl From<&str> for RuntimeError {
+    fn from(message: &str) -> Self {
+        RuntimeError::from(message.to_string())
+    }
+}
+
+impl Display for RuntimeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Error for RuntimeError {}
@@ -0,0 +1,349 @@
+pub mod builtins;
+pub mod environment;
+pub mod error;
+pub mod object;
+
+use crate::ast::{scope, BlockStatement, Expression, Program, Statement};
+use crate::evaluator::builtins::Builtin;
+use crate::evaluator::environment::Environment;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use crate::evaluator::error::RuntimeError;
+use crate::evaluator::object::Object;
+use crate::token::Token;
+use crate::vm;
+
+/// How much native stack must be left before evaluating an expression. With less, evaluation
+/// continues on a newly allocated stack segment of `STACK_SEGMENT_SIZE` bytes.
+const STACK_RED_ZONE: usize = 256 * 1024;
+const STACK_SEGMENT_SIZE: usize = 4 * 1024 * 1024;
+
+/// Evaluates programs by walking their syntax tree, writing what `puts` prints to `output`.
+///
+/// The evaluator recurses natively for every nested expression and Monkey call. It grows the
+/// native stack as it goes, so it can run on a thread with a default-sized stack, and a program
+/// that nests calls more than `vm::MAX_FRAMES` deep fails with a stack overflow error as it does on
+/// the VM.
+pub struct Evaluator<'a> {
+    output: &'a mut dyn Write,
+    /// How many calls of Monkey functions are running, which may not exceed `vm::MAX_FRAMES`.
+    depth: usize,
+}
+
+pub fn new(output: &mut dyn Write) -> Evaluator<'_> {
+    Evaluator { output, depth: 0 }
+}
+
+/// Evaluates `program` in `environment`, printing the output of `puts` to standard output. This
+/// can be called from any thread: deep recursion allocates more native stack rather than
+/// overflowing the caller's.
+pub fn eval(program: &Program, environment: &mut Environment) -> Object {
+    eval_with_output(program, environment, &mut io::stdout())
+}
+
+/// Evaluates `program` like `eval`, writing the output of `puts` to `output`.
+pub fn eval_with_output(program: &Program, environment: &mut Environment, output: &mut dyn Write) -> Object {
+    new(output).eval(program, environment)
+}
+
+impl Evaluator<'_> {
+    pub fn eval(&mut self, program: &Program, environment: &mut Environment) -> Object {
+        let mut result = Object::Null;
+
+        for statement in program.statements.iter() {
+            result = self.eval_statement(statement, environment);
+
+            match result {
+                Object::ReturnValue(value) => return *value,
+                Object::Error(_) => return result,
+                _ => {}
+            }
+        }
+
+        return result;
+    }
+
+    fn eval_block_statement(&mut self, block: &BlockStatement, environment: &mut Environment) -> Object {
+        let mut result = Object::Null;
+
+        for statement in block.statements.iter() {
+            result = self.eval_statement(statement, environment);
+
+            if let Object::ReturnValue(_) | Object::Error(_) = result {
+                return result;
+            }
+        }
+
+        return result;
+    }
+
+    fn eval_statement(&mut self, statement: &Statement, environment: &mut Environment) -> Object {
+        match statement {
+            Statement::Let(let_statement) => {
+                let value = self.eval_expression(&let_statement.value, environment);
+                if value.is_error() {
+                    return value;
+                }
+                environment.set(let_statement.identifier_name.clone(), value);
+                Object::Null
+            }
+            Statement::Return(return_statement) => {
+                let value = self.eval_expression(&return_statement.value, environment);
+                if value.is_error() {
+                    return value;
+                }
+                Object::ReturnValue(Box::new(value))
+            }
+            Statement::Expression(expression) => self.eval_expression(expression, environment),
+            Statement::Error(span) => Object::Error(RuntimeError::from("syntax error").at(*span)),
+        }
+    }
+
+    /// Evaluates `expression`, attributing an error it raises itself, rather than one passed up from a
+    /// subexpression, to its span.
+    fn eval_expression(&mut self, expression: &Expression, environment: &mut Environment) -> Object {
+        let result = stacker::maybe_grow(STACK_RED_ZONE, STACK_SEGMENT_SIZE, || {
+            self.eval_expression_node(expression, environment)
+        });
+        match result {
+            Object::Error(error) if error.span.is_none() => Object::Error(error.at(expression.span())),
+            result => result,
+        }
+    }
+
+    fn eval_expression_node(&mut self, expression: &Expression, environment: &mut Environment) -> Object {
+        match expression {
+            Expression::Identifier { token, .. } => eval_identifier(token, environment),
+            Expression::Boolean { value, .. } => Object::Boolean(*value),
+            Expression::IntegerLiteral { value, .. } => Object::Integer(*value),
+            Expression::StringLiteral { value, .. } => Object::Str(value.clone()),
+            Expression::PrefixExpression { operator, right, .. } => {
+                let right = self.eval_expression(right, environment);
+                if right.is_error() {
+                    return right;
+                }
+                eval_prefix_expression(operator, right)
+            }
+            Expression::InfixExpression { operator, left, right, .. } => {
+                let left = self.eval_expression(left, environment);
+                if left.is_error() {
+                    return left;
+                }
+                let right = self.eval_expression(right, environment);
+                if right.is_error() {
+                    return right;
+                }
+                eval_infix_expression(operator, left, right)
+            }
+            Expression::IfExpression { condition, consequence, alternative, .. } => {
+                let condition = self.eval_expression(condition, environment);
+                if condition.is_error() {
+                    return condition;
+                }
+
+                let branch = match condition.is_truthy() {
+                    true => consequence,
+                    false => alternative,
+                };
+
+                match branch {
+                    Some(block) => self.eval_block_statement(block, environment),
+                    None => Object::Null,
+                }
+            }
+            Expression::Function { parameters, body, .. } => Object::Function {
+                parameters: parameters.clone(),
+                body: body.clone(),
+                environment: environment.clone(),
+            },
+            Expression::Call { function, arguments, .. } => {
+                let function = self.eval_expression(function, environment);
+                if function.is_error() {
+                    return function;
+                }
+
+                let arguments = match self.eval_expressions(arguments, environment) {
+                    Ok(arguments) => arguments,
+                    Err(error) => return error,
+                };
+
+                self.apply_function(function, arguments)
+            }
+            Expression::ArrayLiteral { elements, .. } => match self.eval_expressions(elements, environment) {
+                Ok(elements) => Object::Array(elements),
+                Err(error) => error,
+            },
+            Expression::Index { left, index, .. } => {
+                let left = self.eval_expression(left, environment);
+                if left.is_error() {
+                    return left;
+                }
+                let index = self.eval_expression(index, environment);
+                if index.is_error() {
+                    return index;
+                }
+                eval_index_expression(left, index)
+            }
+            Expression::HashLiteral { pairs, .. } => self.eval_hash_literal(pairs, environment),
+            Expression::Error { .. } => Object::error("syntax error".into()),
+        }
+    }
+
+    fn eval_hash_literal(&mut self, pairs: &[(Expression, Expression)], environment: &mut Environment) -> Object {
+        let mut hash = BTreeMap::new();
+
+        for (key, value) in pairs.iter() {
+            let key = self.eval_expression(key, environment);
+            if key.is_error() {
+                return key;
+            }
+
+            let hash_key = match key.hash_key() {
+                Some(hash_key) => hash_key,
+                None => return Object::error(format!("unusable as hash key: {}", key.type_name())),
+            };
+
+            let value = self.eval_expression(value, environment);
+            if value.is_error() {
+                return value;
+            }
+
+            hash.insert(hash_key, value);
+        }
+
+        return Object::Hash(hash);
+    }
+
+    fn eval_expressions(&mut self, expressions: &[Expression], environment: &mut Environment) -> Result<Vec<Object>, Object> {
+        let mut results = Vec::with_capacity(expressions.len());
+        for expression in expressions.iter() {
+            let result = self.eval_expression(expression, environment);
+            if result.is_error() {
+                return Err(result);
+            }
+            results.push(result);
+        }
+        return Ok(results);
+    }
+
+    /// Calls `function`. The body of a Monkey function runs in a new scope enclosed by the environment
+    /// the function was defined in, not the one it is called from.
+    fn apply_function(&mut self, function: Object, arguments: Vec<Object>) -> Object {
+        match function {
+            Object::Function { parameters, body, environment } => {
+                if parameters.len() != arguments.len() {
+                    return Object::error(format!(
+                        "wrong number of arguments: expected {}, got {}", parameters.len(), arguments.len()
+                    ));
+                }
+                if self.depth >= vm::MAX_FRAMES {
+                    return Object::error(format!("stack overflow: more than {} nested calls", vm::MAX_FRAMES));
+                }
+
+                let mut function_environment = environment::new_enclosed(environment);
+                for (parameter, argument) in parameters.iter().zip(arguments) {
+                    if let Expression::Identifier { token: Token::Identifier { literal }, .. } = parameter {
+                        function_environment.set(literal.clone(), argument);
+                    }
+                }
+                for name in scope::let_names(&body.statements) {
+                    function_environment.declare(name);
+                }
+
+                self.depth += 1;
+                let result = self.eval_block_statement(&body, &mut function_environment);
+                self.depth -= 1;
+                match result {
+                    Object::ReturnValue(value) => *value,
+                    result => result,
+                }
+            }
+            Object::Builtin(builtin) => builtin.call(arguments, self.output),
+            _ => Object::error(format!("not a function: {}", function.type_name())),
+        }
+    }
+}
+
+pub(crate) fn eval_index_expression(left: Object, index: Object) -> Object {
+    match (left, index) {
+        (Object::Array(elements), Object::Integer(index)) => usize::try_from(index)
+            .ok()
+            .and_then(|index| elements.get(index).cloned())
+            .unwrap_or(Object::Null),
+        (Object::Hash(pairs), index) => match index.hash_key() {
+            Some(hash_key) => pairs.get(&hash_key).cloned().unwrap_or(Object::Null),
+            None => Object::error(format!("unusable as hash key: {}", index.type_name())),
+        },
+        (left, index) => Object::error(
+            format!("index operator not supported: {}[{}]", left.type_name(), index.type_name())
+        ),
+    }
+}
+
+fn eval_identifier(token: &Token, environment: &Environment) -> Object {
+    match token {
+        Token::Identifier { literal } => environment.get(literal)
+            .or_else(|| Builtin::lookup(literal).map(Object::Builtin))
+            .unwrap_or_else(|| Object::error(format!("identifier not found: {}", literal))),
+        _ => Object::error(format!("not an identifier: {:?}", token)),
+    }
+}
+
+pub(crate) fn eval_prefix_expression(operator: &str, right: Object) -> Object {
+    match (operator, right) {
+        ("!", right) => Object::Boolean(!right.is_truthy()),
+        ("-", Object::Integer(value)) => match value.checked_neg() {
+            Some(value) => Object::Integer(value),
+            None => Object::error(format!("integer overflow: -{}", value)),
+        },
+        ("+", Object::Integer(value)) => Object::Integer(value),
+        (operator, right) => Object::error(format!("unknown operator: {}{}", operator, right.type_name())),
+    }
+}
+
+pub(crate) fn eval_infix_expression(operator: &str, left: Object, right: Object) -> Object {
+    match (left, right) {
+        (Object::Integer(left), Object::Integer(right)) => eval_integer_infix_expression(operator, left, right),
+        (Object::Str(left), Object::Str(right)) => match operator {
+            "+" => Object::Str(left + &right),
+            "==" => Object::Boolean(left == right),
+            "!=" => Object::Boolean(left != right),
+            _ => Object::error(format!("unknown operator: STRING {} STRING", operator)),
+        },
+        (Object::Boolean(left), Object::Boolean(right)) => match operator {
+            "==" => Object::Boolean(left == right),
+            "!=" => Object::Boolean(left != right),
+            _ => Object::error(format!("unknown operator: BOOLEAN {} BOOLEAN", operator)),
+        },
+        (left, right) if left.type_name() != right.type_name() => Object::error(
+            format!("type mismatch: {} {} {}", left.type_name(), operator, right.type_name())
+        ),
+        (left, right) => Object::error(
+            format!("unknown operator: {} {} {}", left.type_name(), operator, right.type_name())
+        ),
+    }
+}
+
+fn eval_integer_infix_expression(operator: &str, left: i64, right: i64) -> Object {
+    let result = match operator {
+        "+" => left.checked_add(right),
+        "-" => left.checked_sub(right),
+        "*" => left.checked_mul(right),
+        "/" => {
+            if right == 0 {
+                return Object::error(format!("division by zero: {} / {}", left, right));
+            }
+            left.checked_div(right)
+        }
+        "<" => return Object::Boolean(left < right),
+        ">" => return Object::Boolean(left > right),
+        "==" => return Object::Boolean(left == right),
+        "!=" => return Object::Boolean(left != right),
+        _ => return Object::error(format!("unknown operator: INTEGER {} INTEGER", operator)),
+    };
+
+    match result {
+        Some(value) => Object::Integer(value),
+        None => Object::error(format!("integer overflow: {} {} {}", left, operator, right)),
+    }
+}
@@ -0,0 +1,165 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::rc::Rc;
+use crate::ast::{BlockStatement, Expression};
+use crate::compiler::CompiledFunction;
+use crate::evaluator::builtins::Builtin;
+use crate::evaluator::environment::Environment;
+use crate::evaluator::error::RuntimeError;
+
+pub mod type_name {
+    pub const INTEGER: &str = "INTEGER";
+    pub const BOOLEAN: &str = "BOOLEAN";
+    pub const STRING: &str = "STRING";
+    pub const ARRAY: &str = "ARRAY";
+    pub const HASH: &str = "HASH";
+    pub const NULL: &str = "NULL";
+    pub const RETURN_VALUE: &str = "RETURN_VALUE";
+    pub const ERROR: &str = "ERROR";
+    pub const FUNCTION: &str = "FUNCTION";
+    pub const BUILTIN: &str = "BUILTIN";
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Object {
+    Integer(i64),
+    Boolean(bool),
+    Str(String),
+    Array(Vec<Object>),
+    Hash(BTreeMap<HashKey, Object>),
+    Null,
+    ReturnValue(Box<Object>),
+    Error(RuntimeError),
+    /// A function value created by the evaluator, with the environment it was defined in.
+    Function { parameters: Vec<Expression>, body: BlockStatement, environment: Environment },
+    Builtin(Builtin),
+    /// A function value created by the VM.
+    Closure(Rc<Closure>),
+}
+
+/// The local variables of one call of a compiled function by slot, shared with the closures that
+/// capture them. A slot is `None` until its `let` has run.
+pub type Locals = Rc<RefCell<Vec<Option<Object>>>>;
+
+/// A compiled function together with the variables it shares with the functions around it.
+#[derive(Clone, PartialEq)]
+pub struct Closure {
+    pub function: Rc<CompiledFunction>,
+    pub free: Vec<Capture>,
+}
+
+/// A variable a closure shares with the function that created it.
+#[derive(Clone)]
+pub enum Capture {
+    /// The slot of the locals of a call.
+    Shared(Locals, usize),
+    /// The slot of the locals that hold the closure itself. The closure is stored this way so that
+    /// it and the locals do not keep each other alive, and gets the locals back when it is read.
+    Own(usize),
+}
+
+/// Two captures are equal when they are the same variable.
+impl PartialEq for Capture {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Capture::Shared(left, left_slot), Capture::Shared(right, right_slot)) => {
+                Rc::ptr_eq(left, right) && left_slot == right_slot
+            }
+            (Capture::Own(left_slot), Capture::Own(right_slot)) => left_slot == right_slot,
+            _ => false,
+        }
+    }
+}
+
+/// Leaves out the values of the free variables, since they may hold the closure itself.
+impl std::fmt::Debug for Closure {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Closure")
+            .field("function", &self.function.name)
+            .field("free", &self.function.free.iter().map(|symbol| &symbol.name).collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl Object {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Object::Integer(_) => type_name::INTEGER,
+            Object::Boolean(_) => type_name::BOOLEAN,
+            Object::Str(_) => type_name::STRING,
+            Object::Array(_) => type_name::ARRAY,
+            Object::Hash(_) => type_name::HASH,
+            Object::Null => type_name::NULL,
+            Object::ReturnValue(_) => type_name::RETURN_VALUE,
+            Object::Error(_) => type_name::ERROR,
+            Object::Function { .. } | Object::Closure(_) => type_name::FUNCTION,
+            Object::Builtin(_) => type_name::BUILTIN,
+        }
+    }
+
+    pub fn is_truthy(&self) -> bool {
+        !matches!(self, Object::Null | Object::Boolean(false))
+    }
+
+    pub fn error(message: String) -> Object {
+        Object::Error(RuntimeError::from(message))
+    }
+
+    pub fn is_error(&self) -> bool {
+        matches!(self, Object::Error(_))
+    }
+
+    pub fn hash_key(&self) -> Option<HashKey> {
+        match self {
+            Object::Integer(value) => Some(HashKey::Integer(*value)),
+            Object::Boolean(value) => Some(HashKey::Boolean(*value)),
+            Object::Str(value) => Some(HashKey::Str(value.clone())),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum HashKey {
+    Integer(i64),
+    Boolean(bool),
+    Str(String),
+}
+
+impl Display for HashKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HashKey::Integer(value) => write!(f, "{}", value),
+            HashKey::Boolean(value) => write!(f, "{}", value),
+            HashKey::Str(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+impl Display for Object {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Object::Integer(value) => write!(f, "{}", value),
+            Object::Boolean(value) => write!(f, "{}", value),
+            Object::Str(value) => write!(f, "{}", value),
+            Object::Array(elements) => {
+                let elements: Vec<String> = elements.iter().map(|element| element.to_string()).collect();
+                write!(f, "[{}]", elements.join(", "))
+            }
+            Object::Hash(pairs) => {
+                let pairs: Vec<String> = pairs.iter().map(|(key, value)| format!("{}: {}", key, value)).collect();
+                write!(f, "{{{}}}", pairs.join(", "))
+            }
+            Object::Null => write!(f, "null"),
+            Object::ReturnValue(value) => value.fmt(f),
+            Object::Error(message) => write!(f, "ERROR: {}", message),
+            Object::Function { parameters, .. } => {
+                let parameter_names: Vec<String> = parameters.iter().map(|parameter| parameter.to_string()).collect();
+                write!(f, "fn({}) {{ ... }}", parameter_names.join(", "))
+            }
+            Object::Builtin(_) => write!(f, "builtin function"),
+            Object::Closure(closure) => write!(f, "fn({}) {{ ... }}", closure.function.parameters.join(", ")),
+        }
+    }
+}
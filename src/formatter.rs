@@ -0,0 +1,56 @@
+use crate::ast::printer;
+use crate::lexer::LexerOptions;
+use crate::parser::error::ParseError;
+use crate::token::{Token, Trivia};
+use crate::{lexer, parser};
+
+#[derive(Debug, Clone, Copy)]
+pub struct FormatOptions {
+    /// The column past which argument lists, array literals and the like are wrapped one element per line.
+    pub line_width: usize,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        FormatOptions { line_width: 100 }
+    }
+}
+
+pub fn format(source_code: &str) -> Result<String, Vec<ParseError>> {
+    format_with_options(source_code, FormatOptions::default())
+}
+
+/// Rewrites Monkey source in the canonical style: four-space indented blocks, single spaces around
+/// infix operators, a `;` after every statement and at most one blank line between statements.
+/// Comments are kept where they were written, except that one after the last operand of an
+/// expression moves to the end of its line or into the block that follows, as described on
+/// `Printer::with_comments`. Source that does not parse is returned as the parser's errors.
+pub fn format_with_options(source_code: &str, options: FormatOptions) -> Result<String, Vec<ParseError>> {
+    let mut parser = parser::new(lexer::new(source_code.to_string()));
+    let program = parser.parse()?;
+
+    let mut printer = printer::new(String::new(), false)
+        .with_line_width(options.line_width)
+        .with_comments(source_code, comments(source_code));
+    let _ = printer.print_program(&program);
+
+    let mut formatted = printer.into_inner();
+    if !formatted.is_empty() {
+        formatted.push('\n');
+    }
+    return Ok(formatted);
+}
+
+fn comments(source_code: &str) -> Vec<Trivia> {
+    let options = LexerOptions { preserve_comments: true };
+    let mut lexer = lexer::new_with_options(source_code.to_string(), options);
+    let mut comments = Vec::new();
+
+    while let Ok(spanned_token) = lexer.next_token() {
+        comments.extend(spanned_token.leading_trivia);
+        if spanned_token.token == Token::Eof {
+            break;
+        }
+    }
+    return comments;
+}
@@ -0,0 +1,44 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use crate::token::Span;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LexError {
+    EndOfInput,
+    UnterminatedString { span: Span },
+    UnterminatedComment { span: Span },
+    InvalidEscape { sequence: String, span: Span },
+}
+
+impl LexError {
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            LexError::EndOfInput => None,
+            LexError::UnterminatedString { span }
+            | LexError::UnterminatedComment { span }
+            | LexError::InvalidEscape { span, .. } => Some(*span),
+        }
+    }
+
+    /// The error without its location.
+    pub fn message(&self) -> String {
+        match self {
+            LexError::EndOfInput => "source code already tokenized".into(),
+            LexError::UnterminatedString { .. } => "unterminated string literal".into(),
+            LexError::UnterminatedComment { .. } => "unterminated block comment".into(),
+            LexError::InvalidEscape { sequence, .. } => format!("invalid escape sequence `{}`", sequence),
+        }
+    }
+}
+
+impl Display for LexError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message())?;
+        match self.span() {
+            Some(span) => write!(f, " at {}:{}", span.line, span.column),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Error for LexError {}
@@ -0,0 +1,331 @@
+pub mod error;
+
+use unicode_xid::UnicodeXID;
+use crate::lexer::error::LexError;
+use crate::token::{Span, SpannedToken, Token, Trivia, TriviaKind};
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LexerOptions {
+    /// Keep comments as `leading_trivia` on the token that follows them instead of discarding them.
+    pub preserve_comments: bool,
+}
+
+/// Tokenizes Monkey source one `char` at a time. `current_index` and `line_start_index` count
+/// characters, while the spans handed out carry byte offsets into the source and character columns.
+pub struct Lexer {
+    characters: Vec<(usize, char)>,
+    source_length: usize,
+    options: LexerOptions,
+    current_index: usize,
+    line: usize,
+    line_start_index: usize,
+}
+
+impl Lexer {
+    pub fn next_token(&mut self) -> Result<SpannedToken, LexError> {
+        let leading_trivia = self.skip_trivia()?;
+
+        let len = self.characters.len();
+
+        if self.current_index > len {
+            return Err(LexError::EndOfInput);
+        }
+
+        let start_span = self.start_span();
+
+        if self.current_index == len {
+            self.current_index += 1;
+            return Ok(SpannedToken { token: Token::Eof, span: start_span, leading_trivia });
+        }
+
+        let character = self.get_char(self.current_index);
+
+        let token = match character {
+            '(' => Token::LeftParenthesis,
+            ')' => Token::RightParenthesis,
+            '+' => Token::Plus,
+            '-' => Token::Minus,
+            ',' => Token::Comma,
+            ':' => Token::Colon,
+            ';' => Token::Semicolon,
+            '{' => Token::LeftBracket,
+            '}' => Token::RightBracket,
+            '[' => Token::LeftSquare,
+            ']' => Token::RightSquare,
+            '/' => Token::Slash,
+            '*' => Token::Asterisk,
+            '<' => Token::LessThan,
+            '>' => Token::GreaterThan,
+            '=' => {
+                if self.next_char() == '=' {
+                    self.current_index += 1;
+                    Token::Equal
+                } else {
+                    Token::Assign
+                }
+            }
+            '!' => {
+                if self.next_char() == '=' {
+                    self.current_index += 1;
+                    Token::NotEqual
+                } else {
+                    Token::Bang
+                }
+            }
+            '"' => self.read_string(start_span)?,
+            c if c.is_ascii_digit() => self.read_number(),
+            c if is_identifier_start(c) => self.read_identifier(),
+            _ => Token::Illegal
+        };
+
+        self.current_index += 1;
+        Ok(SpannedToken { token, span: self.finish_span(start_span), leading_trivia })
+    }
+
+    fn start_span(&self) -> Span {
+        let start = self.byte_offset(self.current_index);
+        Span {
+            start,
+            end: start,
+            line: self.line,
+            column: self.current_index - self.line_start_index + 1,
+        }
+    }
+
+    fn finish_span(&self, start_span: Span) -> Span {
+        Span { end: self.byte_offset(self.current_index), ..start_span }
+    }
+
+    fn byte_offset(&self, index: usize) -> usize {
+        match self.characters.get(index) {
+            Some((byte_offset, _)) => *byte_offset,
+            None => self.source_length,
+        }
+    }
+
+    fn skip_trivia(&mut self) -> Result<Vec<Trivia>, LexError> {
+        let mut trivia = vec![];
+
+        loop {
+            self.skip_whitespace();
+
+            let kind = match (self.current_char(), self.next_char()) {
+                ('/', '/') => TriviaKind::LineComment,
+                ('/', '*') => TriviaKind::BlockComment,
+                _ => return Ok(trivia),
+            };
+
+            let start_span = self.start_span();
+            let start_index = self.current_index;
+            match kind {
+                TriviaKind::LineComment => self.skip_line_comment(),
+                TriviaKind::BlockComment => self.skip_block_comment(start_span)?,
+            }
+
+            if self.options.preserve_comments {
+                trivia.push(Trivia {
+                    kind,
+                    text: self.characters[start_index..self.current_index].iter().map(|(_, c)| c).collect(),
+                    span: self.finish_span(start_span),
+                });
+            }
+        }
+    }
+
+    fn skip_line_comment(&mut self) {
+        while self.current_index < self.characters.len() && self.current_char() != '\n' {
+            self.current_index += 1;
+        }
+    }
+
+    /// Skips a `/* ... */` comment, which may contain nested block comments.
+    fn skip_block_comment(&mut self, start_span: Span) -> Result<(), LexError> {
+        let mut depth = 0;
+
+        while self.current_index < self.characters.len() {
+            match (self.current_char(), self.next_char()) {
+                ('/', '*') => {
+                    depth += 1;
+                    self.current_index += 2;
+                }
+                ('*', '/') => {
+                    depth -= 1;
+                    self.current_index += 2;
+                    if depth == 0 {
+                        return Ok(());
+                    }
+                }
+                ('\n', _) => {
+                    self.current_index += 1;
+                    self.line += 1;
+                    self.line_start_index = self.current_index;
+                }
+                _ => self.current_index += 1,
+            }
+        }
+
+        Err(LexError::UnterminatedComment { span: self.finish_span(start_span) })
+    }
+
+    fn skip_whitespace(&mut self) {
+        let len = self.characters.len();
+        while self.current_index < len && self.current_char().is_whitespace() {
+            if self.current_char() == '\n' {
+                self.line += 1;
+                self.line_start_index = self.current_index + 1;
+            }
+            self.current_index += 1
+        }
+    }
+
+    fn get_char(&self, index: usize) -> char {
+        match self.characters.get(index) {
+            Some((_, character)) => *character,
+            None => '\0',
+        }
+    }
+
+    fn current_char(&self) -> char {
+        self.get_char(self.current_index)
+    }
+
+    fn next_char(&self) -> char {
+        self.get_char(self.current_index + 1)
+    }
+
+    fn read_string(&mut self, start_span: Span) -> Result<Token, LexError> {
+        let mut literal = String::new();
+        let mut escape_error = None;
+
+        loop {
+            self.current_index += 1;
+            if self.current_index >= self.characters.len() {
+                return Err(LexError::UnterminatedString { span: self.finish_span(start_span) });
+            }
+
+            match self.current_char() {
+                '"' => break,
+                '\\' => {
+                    let escape_span = self.start_span();
+                    self.current_index += 1;
+                    if self.current_index >= self.characters.len() {
+                        return Err(LexError::UnterminatedString { span: self.finish_span(start_span) });
+                    }
+                    match self.read_escape_sequence(escape_span) {
+                        Ok(character) => literal.push(character),
+                        Err(err) => {
+                            escape_error.get_or_insert(err);
+                        }
+                    }
+                }
+                '\n' => {
+                    self.line += 1;
+                    self.line_start_index = self.current_index + 1;
+                    literal.push('\n');
+                }
+                character => literal.push(character),
+            }
+        }
+
+        if let Some(err) = escape_error {
+            self.current_index += 1;
+            return Err(err);
+        }
+
+        Ok(Token::Str { literal })
+    }
+
+    fn read_escape_sequence(&mut self, escape_span: Span) -> Result<char, LexError> {
+        match self.current_char() {
+            'n' => Ok('\n'),
+            't' => Ok('\t'),
+            'r' => Ok('\r'),
+            '0' => Ok('\0'),
+            '"' => Ok('"'),
+            '\\' => Ok('\\'),
+            'u' => self.read_unicode_escape(escape_span),
+            character => Err(self.invalid_escape(format!("\\{}", character), escape_span)),
+        }
+    }
+
+    fn read_unicode_escape(&mut self, escape_span: Span) -> Result<char, LexError> {
+        if self.next_char() != '{' {
+            return Err(self.invalid_escape("\\u".into(), escape_span));
+        }
+        self.current_index += 1;
+
+        let mut digits = String::new();
+        while self.next_char().is_ascii_hexdigit() && digits.len() < 6 {
+            self.current_index += 1;
+            digits.push(self.current_char());
+        }
+
+        if self.next_char() != '}' {
+            return Err(self.invalid_escape(format!("\\u{{{}", digits), escape_span));
+        }
+        self.current_index += 1;
+
+        u32::from_str_radix(&digits, 16)
+            .ok()
+            .and_then(char::from_u32)
+            .ok_or_else(|| self.invalid_escape(format!("\\u{{{}}}", digits), escape_span))
+    }
+
+    fn invalid_escape(&self, sequence: String, escape_span: Span) -> LexError {
+        LexError::InvalidEscape {
+            sequence,
+            span: Span { end: self.byte_offset(self.current_index + 1), ..escape_span },
+        }
+    }
+
+    fn read_number(&mut self) -> Token {
+        let mut literal = String::from(self.current_char());
+        while self.next_char().is_ascii_digit() {
+            self.current_index += 1;
+            literal.push(self.current_char());
+        }
+        Token::Int { literal }
+    }
+
+    fn read_identifier(&mut self) -> Token {
+        let mut literal = String::from(self.current_char());
+        while is_identifier_continue(self.next_char()) {
+            self.current_index += 1;
+            literal.push(self.current_char());
+        }
+
+        match literal.as_str() {
+            "if" => Token::If,
+            "else" => Token::Else,
+            "fn" => Token::Function,
+            "let" => Token::Let,
+            "return" => Token::Return,
+            "true" => Token::True,
+            "false" => Token::False,
+            _ => Token::Identifier { literal },
+        }
+    }
+}
+
+fn is_identifier_start(character: char) -> bool {
+    character == '_' || character.is_xid_start()
+}
+
+fn is_identifier_continue(character: char) -> bool {
+    character.is_xid_continue()
+}
+
+pub fn new(source_code: String) -> Lexer {
+    new_with_options(source_code, LexerOptions::default())
+}
+
+pub fn new_with_options(source_code: String, options: LexerOptions) -> Lexer {
+    Lexer {
+        characters: source_code.char_indices().collect(),
+        source_length: source_code.len(),
+        options,
+        current_index: 0,
+        line: 1,
+        line_start_index: 0,
+    }
+}
@@ -2,4 +2,12 @@ pub mod lexer;
 pub mod token;
 pub mod repl;
 pub mod ast;
-pub mod parser;
\ No newline at end of file
+pub mod parser;
+pub mod evaluator;
+pub mod optimizer;
+pub mod compiler;
+pub mod vm;
+pub mod formatter;
+pub mod diagnostic;
+pub mod serialize;
+pub mod cli;
@@ -1,5 +1,15 @@
-use tarzan::repl;
+use std::io::IsTerminal;
+use std::{env, io, process};
+use tarzan::cli;
+use tarzan::diagnostic::Style;
 
 fn main() {
-    repl::start();
+    let args: Vec<String> = env::args().skip(1).collect();
+    let style = match io::stderr().is_terminal() && env::var_os("NO_COLOR").is_none() {
+        true => Style::Ansi,
+        false => Style::Plain,
+    };
+    let stdin = io::stdin();
+    let code = cli::run(&args, stdin.lock(), &mut io::stdout(), &mut io::stderr(), style);
+    process::exit(code);
 }
@@ -0,0 +1,132 @@
+use std::fmt::{Display, Formatter};
+use crate::ast::fold::{self, Folder};
+use crate::ast::{scope, BlockStatement, Expression, Program};
+use crate::evaluator;
+use crate::evaluator::object::Object;
+use crate::token::{Span, Token};
+
+/// Code the optimizer found that is certain to fail at runtime if it is reached.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Warning {
+    DivisionByZero { span: Span },
+}
+
+impl Warning {
+    pub fn span(&self) -> Span {
+        match self {
+            Warning::DivisionByZero { span } => *span,
+        }
+    }
+
+    /// The warning without its location.
+    pub fn message(&self) -> String {
+        match self {
+            Warning::DivisionByZero { .. } => "division by zero".to_string(),
+        }
+    }
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let span = self.span();
+        write!(f, "{} at {}:{}", self.message(), span.line, span.column)
+    }
+}
+
+/// Simplifies a syntax tree without changing what it evaluates to. Prefix and infix operators
+/// whose operands are integer or boolean literals are replaced by their result, and the branch of
+/// an `if` that a literal condition rules out is dropped, unless it binds names with `let`, which
+/// belong to the enclosing function whether or not the branch runs. Operations that would fail at runtime,
+/// such as an overflowing addition, are left in place so that they still fail there, and a
+/// division by the literal zero is reported as a warning.
+///
+/// Operators are folded by the evaluator's own implementation, so a folded value is always the
+/// one the program would have computed.
+#[derive(Default)]
+pub struct Optimizer {
+    warnings: Vec<Warning>,
+}
+
+pub fn new() -> Optimizer {
+    Optimizer::default()
+}
+
+pub fn optimize(program: Program) -> (Program, Vec<Warning>) {
+    new().optimize(program)
+}
+
+impl Optimizer {
+    pub fn optimize(mut self, program: Program) -> (Program, Vec<Warning>) {
+        let program = self.fold_program(program);
+        return (program, self.warnings);
+    }
+}
+
+impl Folder for Optimizer {
+    fn fold_expression(&mut self, expression: Expression) -> Expression {
+        match expression {
+            Expression::PrefixExpression { operator, right, span } => {
+                let right = self.fold_expression(*right);
+                let folded = constant(&right)
+                    .and_then(|value| literal(evaluator::eval_prefix_expression(&operator, value), span));
+                folded.unwrap_or(Expression::PrefixExpression { operator, right: Box::new(right), span })
+            }
+            Expression::InfixExpression { operator, left, right, span } => {
+                let left = self.fold_expression(*left);
+                let right = self.fold_expression(*right);
+                if operator == "/" && constant(&right) == Some(Object::Integer(0)) {
+                    self.warnings.push(Warning::DivisionByZero { span });
+                    return Expression::InfixExpression { operator, left: Box::new(left), right: Box::new(right), span };
+                }
+
+                let folded = match (constant(&left), constant(&right)) {
+                    (Some(left), Some(right)) => literal(evaluator::eval_infix_expression(&operator, left, right), span),
+                    _ => None,
+                };
+                folded.unwrap_or(Expression::InfixExpression { operator, left: Box::new(left), right: Box::new(right), span })
+            }
+            Expression::IfExpression { token, condition, consequence, alternative, span } => {
+                let condition = self.fold_expression(*condition);
+                let (consequence, alternative) = match constant(&condition).map(|value| value.is_truthy()) {
+                    Some(true) => (consequence, alternative.filter(binds_names)),
+                    Some(false) => (consequence.filter(binds_names), alternative),
+                    None => (consequence, alternative),
+                };
+                Expression::IfExpression {
+                    token,
+                    condition: Box::new(condition),
+                    consequence: consequence.map(|block| self.fold_block_statement(block)),
+                    alternative: alternative.map(|block| self.fold_block_statement(block)),
+                    span,
+                }
+            }
+            expression => fold::fold_expression(self, expression),
+        }
+    }
+}
+
+/// Whether `block` has a `let`, whose name is in scope throughout the enclosing function.
+fn binds_names(block: &BlockStatement) -> bool {
+    !scope::let_names(&block.statements).is_empty()
+}
+
+/// The value of an integer or boolean literal.
+fn constant(expression: &Expression) -> Option<Object> {
+    match expression {
+        Expression::IntegerLiteral { value, .. } => Some(Object::Integer(*value)),
+        Expression::Boolean { value, .. } => Some(Object::Boolean(*value)),
+        _ => None,
+    }
+}
+
+/// The literal for a folded value, or `None` if folding failed and the operation has to stay.
+fn literal(value: Object, span: Span) -> Option<Expression> {
+    match value {
+        Object::Integer(value) => Some(Expression::IntegerLiteral { token: Token::Int { literal: value.to_string() }, value, span }),
+        Object::Boolean(value) => {
+            let token = if value { Token::True } else { Token::False };
+            Some(Expression::Boolean { token, value, span })
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,49 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use crate::lexer::error::LexError;
+use crate::token::{Span, Token};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseError {
+    UnexpectedToken { expected: &'static str, found: Token, span: Span },
+    NoPrefixParser { token: Token, span: Span },
+    IntegerOverflow { literal: String, span: Span },
+    UnclosedDelimiter { delimiter: Token, span: Span },
+    Lexer { error: LexError, span: Span },
+}
+
+impl ParseError {
+    pub fn span(&self) -> Span {
+        match self {
+            ParseError::UnexpectedToken { span, .. }
+            | ParseError::NoPrefixParser { span, .. }
+            | ParseError::IntegerOverflow { span, .. }
+            | ParseError::UnclosedDelimiter { span, .. }
+            | ParseError::Lexer { span, .. } => *span,
+        }
+    }
+
+    /// The error without its location.
+    pub fn message(&self) -> String {
+        match self {
+            ParseError::UnexpectedToken { expected, found: Token::Eof, .. } => format!("expected {}, found end of input", expected),
+            ParseError::UnexpectedToken { expected, found, .. } => format!("expected {}, found `{}`", expected, found),
+            ParseError::NoPrefixParser { token, .. } => format!("no prefix parsing function defined for `{}`", token),
+            ParseError::IntegerOverflow { literal, .. } => format!("integer literal `{}` does not fit in 64 bits", literal),
+            ParseError::UnclosedDelimiter { delimiter, .. } => format!("unclosed delimiter `{}`", delimiter),
+            ParseError::Lexer { error, .. } => error.message(),
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let ParseError::Lexer { error, .. } = self {
+            return error.fmt(f);
+        }
+        let span = self.span();
+        write!(f, "{} at {}:{}", self.message(), span.line, span.column)
+    }
+}
+
+impl Error for ParseError {}
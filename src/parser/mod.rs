@@ -0,0 +1,611 @@
+pub mod error;
+pub mod operator;
+
+use std::collections::HashMap;
+use std::mem::{self, Discriminant};
+use crate::lexer::Lexer;
+use crate::parser::error::ParseError;
+use crate::parser::operator::{InfixOperator, PrefixOperator, PrefixParseFunction, INFIX_OPERATORS, PREFIX_OPERATORS, PREFIX_PARSERS};
+use crate::ast::{BlockStatement, Expression, LetStatement, Program, ReturnStatement, Statement};
+use crate::token::{Span, Token, token_symbol};
+
+pub struct Parser {
+    lexer: Lexer,
+    current_token: Token,
+    peek_token: Token,
+    current_span: Span,
+    peek_span: Span,
+    /// How many `{` enclose `current_token`, used to resynchronize after an error.
+    brace_depth: usize,
+    errors: Vec<ParseError>,
+    token_to_prefix_parse_functions_map: HashMap<Discriminant<Token>, PrefixParseFunction>,
+    token_to_prefix_operator_map: HashMap<Discriminant<Token>, &'static PrefixOperator>,
+    token_to_infix_operator_map: HashMap<Discriminant<Token>, &'static InfixOperator>,
+}
+
+/// How tightly an operator binds, from loosest to tightest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Precedence {
+    Lowest,
+    Equals,
+    LessGreater,
+    Sum,
+    Product,
+    Prefix,
+    Call,
+    Index,
+}
+
+impl Precedence {
+    pub fn value(&self) -> u8 {
+        *self as u8
+    }
+}
+
+pub fn new(lexer: Lexer) -> Parser {
+    with_infix_operators(lexer, INFIX_OPERATORS)
+}
+
+/// Creates a parser that extends expressions with `infix_operators` instead of the language's own.
+pub fn with_infix_operators(lexer: Lexer, infix_operators: &'static [InfixOperator]) -> Parser {
+    let prefix_parsers = PREFIX_PARSERS.iter().map(|prefix| (mem::discriminant(&prefix.token), prefix.parse));
+    let prefix_operators = PREFIX_OPERATORS.iter()
+        .map(|prefix| (mem::discriminant(&prefix.token), Parser::parse_prefix_expression as PrefixParseFunction));
+
+    let mut parser = Parser {
+        lexer,
+        current_token: Token::Illegal,
+        peek_token: Token::Illegal,
+        current_span: Span::default(),
+        peek_span: Span::default(),
+        brace_depth: 0,
+        errors: Vec::new(),
+        token_to_prefix_parse_functions_map: prefix_parsers.chain(prefix_operators).collect(),
+        token_to_prefix_operator_map: PREFIX_OPERATORS.iter().map(|operator| (mem::discriminant(&operator.token), operator)).collect(),
+        token_to_infix_operator_map: infix_operators.iter().map(|operator| (mem::discriminant(&operator.token), operator)).collect(),
+    };
+
+    parser.next_token_n_times(2);
+
+    return parser;
+}
+
+impl Parser {
+    pub fn next_token(&mut self) {
+        match self.current_token {
+            Token::LeftBracket => self.brace_depth += 1,
+            Token::RightBracket => self.brace_depth = self.brace_depth.saturating_sub(1),
+            _ => {}
+        }
+        self.current_token = self.peek_token.clone();
+        self.current_span = self.peek_span;
+        if self.current_token != Token::Eof {
+            match self.lexer.next_token() {
+                Ok(spanned_token) => {
+                    self.peek_token = spanned_token.token;
+                    self.peek_span = spanned_token.span;
+                }
+                Err(error) => {
+                    let span = error.span().unwrap_or(self.current_span);
+                    self.errors.push(ParseError::Lexer { error, span });
+                    self.peek_token = Token::Illegal;
+                    self.peek_span = span;
+                }
+            }
+        }
+    }
+
+    pub fn next_token_n_times(&mut self, n: u32) {
+        for _ in 0..n {
+            self.next_token()
+        }
+    }
+
+    pub fn parse(&mut self) -> Result<Program, Vec<ParseError>> {
+        let (program, errors) = self.parse_with_recovery();
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        return Ok(program);
+    }
+
+    /// Parses the whole input even if it has errors. Each statement that fails to parse becomes a
+    /// `Statement::Error`, or keeps its `let` or `return` with an `Expression::Error` value, and
+    /// parsing resumes at the next `;`, `}` or statement keyword, so that one mistake produces one
+    /// error and the rest of the program is still available to tools.
+    pub fn parse_with_recovery(&mut self) -> (Program, Vec<ParseError>) {
+        let mut statements = Vec::new();
+
+        while self.current_token != Token::Eof {
+            statements.push(self.parse_statement());
+        }
+
+        return (Program { statements }, std::mem::take(&mut self.errors));
+    }
+
+    /// Parses the statement starting at `current_token` and moves on to the token after it.
+    fn parse_statement(&mut self) -> Statement {
+        let start_span = self.current_span;
+        let depth = self.brace_depth;
+        let statement = match self.current_token.clone() {
+            Token::Let => self.parse_let_statement(depth),
+            Token::Return => self.parse_return_statement(depth),
+            _ => self.parse_expression_statement(),
+        };
+
+        if let Some(statement) = statement {
+            return statement;
+        }
+        if self.current_span == start_span {
+            self.next_token();
+        }
+        return Statement::Error(self.synchronize(start_span, depth));
+    }
+
+    /// Skips the rest of a statement that failed to parse, stopping after a `;` or before a `}`,
+    /// `let` or `return` at the statement's own nesting `depth`. Returns the span of everything
+    /// from `start_span` up to the last skipped token.
+    fn synchronize(&mut self, start_span: Span, depth: usize) -> Span {
+        let mut span = start_span;
+        loop {
+            match self.current_token {
+                Token::Eof => return span,
+                Token::Semicolon if self.brace_depth == depth => {
+                    span = span.to(self.current_span);
+                    self.next_token();
+                    return span;
+                }
+                Token::RightBracket if self.brace_depth == depth && depth > 0 => return span,
+                Token::Let | Token::Return if self.brace_depth == depth => return span,
+                _ => {
+                    span = span.to(self.current_span);
+                    self.next_token();
+                }
+            }
+        }
+    }
+
+    fn parse_let_statement(&mut self, depth: usize) -> Option<Statement> {
+        let start_span = self.current_span;
+        if let Token::Identifier { literal } = self.peek_token.clone() {
+            self.next_token();
+
+            if !self.expect_peek(Token::Assign, token_symbol::ASSIGN) {
+                return None;
+            }
+            self.next_token();
+
+            let (value, span) = self.parse_statement_value(start_span, depth);
+            return Some(Statement::Let(
+                LetStatement {
+                    identifier_name: literal,
+                    value,
+                    span,
+                }
+            ));
+        }
+        self.add_err(token_symbol::IDENTIFIER);
+        return None;
+    }
+
+    fn parse_return_statement(&mut self, depth: usize) -> Option<Statement> {
+        let start_span = self.current_span;
+        self.next_token();
+        let (value, span) = self.parse_statement_value(start_span, depth);
+        return Some(Statement::Return(
+            ReturnStatement {
+                value,
+                span,
+            }
+        ));
+    }
+
+    /// Parses the expression of a `let` or `return` statement and moves past it, standing in an
+    /// `Expression::Error` if it fails. Also returns the span of the whole statement.
+    fn parse_statement_value(&mut self, start_span: Span, depth: usize) -> (Expression, Span) {
+        let value_span = self.current_span;
+        match self.parse_expression() {
+            Some(expression) => {
+                let span = start_span.to(self.current_span);
+                self.next_token();
+                (expression, span)
+            }
+            None => {
+                let span = self.synchronize(value_span, depth);
+                (Expression::Error { span }, start_span.to(span))
+            }
+        }
+    }
+
+    fn parse_expression(&mut self) -> Option<Expression> {
+        let expression = self.parse_expression_precedence(Precedence::Lowest)?;
+        if self.peek_token == Token::Semicolon {
+            self.next_token();
+        }
+        return Some(expression);
+    }
+
+    fn parse_expression_statement(&mut self) -> Option<Statement> {
+        let expression = self.parse_expression()?;
+        self.next_token();
+        return Some(Statement::Expression(expression));
+    }
+
+    fn parse_expression_precedence(&mut self, precedence: Precedence) -> Option<Expression> {
+        self.parse_expression_binding_power(precedence.value())
+    }
+
+    /// Parses an expression, continuing through infix operators for as long as they bind more
+    /// tightly than `binding_power`.
+    fn parse_expression_binding_power(&mut self, binding_power: u8) -> Option<Expression> {
+        let prefix_parse_function = self.token_to_prefix_parse_functions_map.get(&mem::discriminant(&self.current_token)).copied();
+        if prefix_parse_function.is_none() {
+            if self.current_token == Token::Illegal && self.has_lexer_error_at(self.current_span) {
+                return None;
+            }
+            self.errors.push(ParseError::NoPrefixParser {
+                token: self.current_token.clone(),
+                span: self.current_span,
+            });
+            return None;
+        }
+
+        let mut left_expression = prefix_parse_function?(self);
+        while !(self.peek_token == Token::Semicolon) {
+            let infix = match self.token_to_infix_operator_map.get(&mem::discriminant(&self.peek_token)) {
+                Some(infix) if binding_power < infix.precedence.value() => *infix,
+                _ => return left_expression,
+            };
+            self.next_token();
+            left_expression = (infix.parse)(self, left_expression?);
+        }
+
+        return left_expression;
+    }
+
+    fn parse_identifier(&mut self) -> Option<Expression> {
+        Some(Expression::Identifier {
+            token: self.current_token.clone(),
+            span: self.current_span,
+        })
+    }
+
+    fn parse_integer_literal_expression(&mut self) -> Option<Expression> {
+        if let Token::Int { literal } = self.current_token.clone() {
+            let value = match literal.parse::<i64>() {
+                Ok(value) => value,
+                Err(_) => {
+                    self.errors.push(ParseError::IntegerOverflow {
+                        literal,
+                        span: self.current_span,
+                    });
+                    return None;
+                }
+            };
+
+            return Some(Expression::IntegerLiteral {
+                token: self.current_token.clone(),
+                value,
+                span: self.current_span,
+            });
+        }
+
+        return None;
+    }
+
+    fn parse_string_literal_expression(&mut self) -> Option<Expression> {
+        if let Token::Str { literal } = self.current_token.clone() {
+            return Some(Expression::StringLiteral {
+                token: self.current_token.clone(),
+                value: literal,
+                span: self.current_span,
+            });
+        }
+
+        return None;
+    }
+
+    fn parse_boolean_expression(&mut self) -> Option<Expression> {
+        let value = match self.current_token {
+            Token::False => Some(false),
+            Token::True => Some(true),
+            _ => None
+        }?;
+        Some(Expression::Boolean {
+            token: self.current_token.clone(),
+            value,
+            span: self.current_span,
+        })
+    }
+
+    fn parse_prefix_expression(&mut self) -> Option<Expression> {
+        let start_span = self.current_span;
+        let operator = *self.token_to_prefix_operator_map.get(&mem::discriminant(&self.current_token))?;
+
+        self.next_token();
+
+        let right = self.parse_expression_precedence(operator.precedence)?;
+
+        return Some(Expression::PrefixExpression {
+            operator: operator.symbol.to_string(),
+            span: start_span.to(right.span()),
+            right: Box::new(right),
+        });
+    }
+
+
+    fn parse_grouped_expression(&mut self) -> Option<Expression> {
+        let start_span = self.current_span;
+        self.next_token();
+        let expression = self.parse_expression_precedence(Precedence::Lowest)?;
+
+        match self.peek_token == Token::RightParenthesis {
+            true => {
+                self.next_token();
+                return Some(expression);
+            }
+            false => {
+                self.errors.push(ParseError::UnclosedDelimiter {
+                    delimiter: Token::LeftParenthesis,
+                    span: start_span,
+                });
+                None
+            }
+        }
+    }
+
+    fn parse_infix_expression(&mut self, left: Expression) -> Option<Expression> {
+        let operator = *self.token_to_infix_operator_map.get(&mem::discriminant(&self.current_token))?;
+        self.next_token();
+        let right = self.parse_expression_binding_power(operator.right_binding_power())?;
+
+        return Some(Expression::InfixExpression {
+            operator: operator.symbol.to_string(),
+            span: left.span().to(right.span()),
+            left: Box::from(left),
+            right: Box::new(right),
+        });
+    }
+
+    fn parse_call_expression(&mut self, function: Expression) -> Option<Expression> {
+        let arguments = self.parse_expression_list(Token::RightParenthesis, token_symbol::COMMA_OR_RIGHT_PARENTHESIS)?;
+
+        return Some(Expression::Call {
+            span: function.span().to(self.current_span),
+            function: Box::new(function),
+            arguments,
+        });
+    }
+
+    fn parse_array_literal(&mut self) -> Option<Expression> {
+        let start_span = self.current_span;
+        let elements = self.parse_expression_list(Token::RightSquare, token_symbol::COMMA_OR_RIGHT_SQUARE)?;
+
+        return Some(Expression::ArrayLiteral {
+            elements,
+            span: start_span.to(self.current_span),
+        });
+    }
+
+    fn parse_hash_literal(&mut self) -> Option<Expression> {
+        let start_span = self.current_span;
+        let mut pairs = vec![];
+
+        while self.peek_token != Token::RightBracket {
+            self.next_token();
+            let key = self.parse_expression_precedence(Precedence::Lowest)?;
+
+            if !self.expect_peek(Token::Colon, token_symbol::COLON) {
+                return None;
+            }
+            self.next_token();
+            let value = self.parse_expression_precedence(Precedence::Lowest)?;
+
+            pairs.push((key, value));
+
+            if self.peek_token != Token::RightBracket && !self.expect_peek(Token::Comma, token_symbol::COMMA_OR_RIGHT_BRACKET) {
+                return None;
+            }
+        }
+        self.next_token();
+
+        return Some(Expression::HashLiteral {
+            pairs,
+            span: start_span.to(self.current_span),
+        });
+    }
+
+    fn parse_index_expression(&mut self, left: Expression) -> Option<Expression> {
+        self.next_token();
+        let index = self.parse_expression_precedence(Precedence::Lowest)?;
+
+        if !self.expect_peek(Token::RightSquare, token_symbol::RIGHT_SQUARE) {
+            return None;
+        }
+
+        return Some(Expression::Index {
+            span: left.span().to(self.current_span),
+            left: Box::new(left),
+            index: Box::new(index),
+        });
+    }
+
+    fn parse_expression_list(&mut self, end: Token, expected: &'static str) -> Option<Vec<Expression>> {
+        let mut expressions = vec![];
+
+        if self.peek_token == end {
+            self.next_token();
+            return Some(expressions);
+        }
+
+        self.next_token();
+        expressions.push(self.parse_expression_precedence(Precedence::Lowest)?);
+
+        while self.peek_token == Token::Comma {
+            self.next_token_n_times(2);
+            expressions.push(self.parse_expression_precedence(Precedence::Lowest)?);
+        }
+
+        if !self.expect_peek(end, expected) {
+            return None;
+        }
+
+        return Some(expressions);
+    }
+
+    fn parse_if_expression(&mut self) -> Option<Expression> {
+        let current_token = self.current_token.clone();
+        let start_span = self.current_span;
+
+        if !self.expect_peek(Token::LeftParenthesis, token_symbol::LEFT_PARENTHESIS) {
+            return None;
+        }
+        self.next_token();
+
+        let condition = self.parse_expression_precedence(Precedence::Lowest)?;
+
+        if !self.expect_peek(Token::RightParenthesis, token_symbol::RIGHT_PARENTHESIS) {
+            return None;
+        }
+
+        if !self.expect_peek(Token::LeftBracket, token_symbol::LEFT_BRACKET) {
+            return None;
+        }
+
+        let consequence = self.parse_block_statement();
+
+        if self.peek_token != Token::Else {
+            return Some(Expression::IfExpression {
+                token: current_token,
+                condition: Box::new(condition),
+                span: start_span.to(consequence.span),
+                consequence: Some(consequence),
+                alternative: None,
+            });
+        }
+        self.next_token();
+
+        if !self.expect_peek(Token::LeftBracket, token_symbol::LEFT_BRACKET) {
+            return None;
+        }
+
+        let alternative = self.parse_block_statement();
+
+        return Some(Expression::IfExpression {
+            token: current_token,
+            condition: Box::new(condition),
+            consequence: Some(consequence),
+            span: start_span.to(alternative.span),
+            alternative: Some(alternative),
+        });
+    }
+
+    fn parse_function_literal(&mut self) -> Option<Expression> {
+        let fn_token = self.current_token.clone();
+        let start_span = self.current_span;
+
+        if !self.expect_peek(Token::LeftParenthesis, token_symbol::LEFT_PARENTHESIS) {
+            return None;
+        }
+
+        let parameters = self.parse_function_parameters()?;
+
+        if !self.expect_peek(Token::LeftBracket, token_symbol::LEFT_BRACKET) {
+            return None;
+        }
+
+        let body = self.parse_block_statement();
+
+        return Some(Expression::Function {
+            token: fn_token,
+            parameters,
+            span: start_span.to(body.span),
+            body,
+        });
+    }
+
+    fn parse_function_parameters(&mut self) -> Option<Vec<Expression>> {
+        let mut parameters = vec![];
+
+        if self.peek_token == Token::RightParenthesis {
+            self.next_token();
+            return Some(parameters);
+        }
+
+        self.next_token();
+        self.add_parameter(&mut parameters)?;
+
+        while self.peek_token == Token::Comma {
+            self.next_token_n_times(2);
+            self.add_parameter(&mut parameters)?;
+        }
+
+        if !self.expect_peek(Token::RightParenthesis, token_symbol::COMMA_OR_RIGHT_PARENTHESIS) {
+            return None;
+        }
+
+        return Some(parameters);
+    }
+
+    fn add_parameter(&mut self, parameters: &mut Vec<Expression>) -> Option<()> {
+        if !matches!(self.current_token, Token::Identifier { .. }) {
+            self.errors.push(ParseError::UnexpectedToken {
+                expected: token_symbol::IDENTIFIER,
+                found: self.current_token.clone(),
+                span: self.current_span,
+            });
+            return None;
+        }
+
+        let parameter = Expression::Identifier {
+            token: self.current_token.clone(),
+            span: self.current_span,
+        };
+        parameters.push(parameter);
+        return Some(());
+    }
+
+    fn parse_block_statement(&mut self) -> BlockStatement {
+        let start_span = self.current_span;
+        let mut statements = Vec::new();
+        self.next_token();
+
+        while self.current_token != Token::RightBracket && self.current_token != Token::Eof {
+            statements.push(self.parse_statement());
+        }
+
+        if self.current_token == Token::Eof {
+            self.errors.push(ParseError::UnclosedDelimiter {
+                delimiter: Token::LeftBracket,
+                span: start_span,
+            });
+        }
+
+        return BlockStatement {
+            statements,
+            span: start_span.to(self.current_span),
+        };
+    }
+
+    fn has_lexer_error_at(&self, span: Span) -> bool {
+        self.errors.iter().any(|error| matches!(error, ParseError::Lexer { span: error_span, .. } if *error_span == span))
+    }
+
+    fn expect_peek(&mut self, token: Token, expected: &'static str) -> bool {
+        if self.peek_token != token {
+            self.add_err(expected);
+            return false;
+        }
+        self.next_token();
+        return true;
+    }
+
+    fn add_err(&mut self, expected: &'static str) {
+        self.errors.push(ParseError::UnexpectedToken {
+            expected,
+            found: self.peek_token.clone(),
+            span: self.peek_span,
+        })
+    }
+}
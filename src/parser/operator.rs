@@ -0,0 +1,132 @@
+use crate::ast::Expression;
+use crate::parser::{Parser, Precedence};
+use crate::token::Token;
+
+pub type PrefixParseFunction = fn(&mut Parser) -> Option<Expression>;
+
+pub type InfixParseFunction = fn(&mut Parser, Expression) -> Option<Expression>;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Associativity {
+    /// `a - b - c` is `(a - b) - c`.
+    Left,
+    /// `a ^ b ^ c` would be `a ^ (b ^ c)`.
+    Right,
+}
+
+/// A token that starts an expression which is not an operator, such as a literal or `if`, and the
+/// function that parses the expression.
+pub struct PrefixParser {
+    pub token: Token,
+    pub parse: PrefixParseFunction,
+}
+
+/// A unary operator written before its operand, with how tightly it binds to that operand.
+pub struct PrefixOperator {
+    pub token: Token,
+    pub symbol: &'static str,
+    pub precedence: Precedence,
+}
+
+/// A token that can follow a complete expression and extend it, such as a binary operator or the
+/// `(` of a call, with how tightly it binds and the function that parses the rest.
+pub struct InfixOperator {
+    pub token: Token,
+    pub symbol: &'static str,
+    pub precedence: Precedence,
+    pub associativity: Associativity,
+    pub parse: InfixParseFunction,
+}
+
+impl InfixOperator {
+    /// The precedence to parse the right operand at. Stopping at operators of the same precedence
+    /// makes the operator left-associative, and continuing through them makes it right-associative.
+    pub fn right_binding_power(&self) -> u8 {
+        match self.associativity {
+            Associativity::Left => self.precedence.value(),
+            Associativity::Right => self.precedence.value() - 1,
+        }
+    }
+}
+
+/// Every token other than a prefix operator that the parser accepts at the start of an
+/// expression. Adding a row here is all it takes to support a new kind of expression.
+pub const PREFIX_PARSERS: &[PrefixParser] = &[
+    PrefixParser { token: Token::Identifier { literal: String::new() }, parse: Parser::parse_identifier },
+    PrefixParser { token: Token::True, parse: Parser::parse_boolean_expression },
+    PrefixParser { token: Token::False, parse: Parser::parse_boolean_expression },
+    PrefixParser { token: Token::Int { literal: String::new() }, parse: Parser::parse_integer_literal_expression },
+    PrefixParser { token: Token::Str { literal: String::new() }, parse: Parser::parse_string_literal_expression },
+    PrefixParser { token: Token::LeftParenthesis, parse: Parser::parse_grouped_expression },
+    PrefixParser { token: Token::LeftSquare, parse: Parser::parse_array_literal },
+    PrefixParser { token: Token::LeftBracket, parse: Parser::parse_hash_literal },
+    PrefixParser { token: Token::If, parse: Parser::parse_if_expression },
+    PrefixParser { token: Token::Function, parse: Parser::parse_function_literal },
+];
+
+/// Every unary operator. The parser and the printer both read their symbols and precedences here.
+pub const PREFIX_OPERATORS: &[PrefixOperator] = &[
+    PrefixOperator { token: Token::Plus, symbol: "+", precedence: Precedence::Prefix },
+    PrefixOperator { token: Token::Minus, symbol: "-", precedence: Precedence::Prefix },
+    PrefixOperator { token: Token::Bang, symbol: "!", precedence: Precedence::Prefix },
+    PrefixOperator { token: Token::LessThan, symbol: "<", precedence: Precedence::Prefix },
+];
+
+/// Every token the parser accepts after a complete expression, from loosest to tightest binding.
+/// The printer reads the same table to decide where parentheses are needed.
+pub const INFIX_OPERATORS: &[InfixOperator] = &[
+    binary(Token::Equal, "==", Precedence::Equals),
+    binary(Token::NotEqual, "!=", Precedence::Equals),
+    binary(Token::LessThan, "<", Precedence::LessGreater),
+    binary(Token::GreaterThan, ">", Precedence::LessGreater),
+    binary(Token::Plus, "+", Precedence::Sum),
+    binary(Token::Minus, "-", Precedence::Sum),
+    binary(Token::Asterisk, "*", Precedence::Product),
+    binary(Token::Slash, "/", Precedence::Product),
+    InfixOperator {
+        token: Token::LeftParenthesis,
+        symbol: "(",
+        precedence: Precedence::Call,
+        associativity: Associativity::Left,
+        parse: Parser::parse_call_expression,
+    },
+    InfixOperator {
+        token: Token::LeftSquare,
+        symbol: "[",
+        precedence: Precedence::Index,
+        associativity: Associativity::Left,
+        parse: Parser::parse_index_expression,
+    },
+];
+
+/// A left-associative binary operator, parsed into an `Expression::InfixExpression`.
+pub const fn binary(token: Token, symbol: &'static str, precedence: Precedence) -> InfixOperator {
+    binary_with(token, symbol, precedence, Associativity::Left)
+}
+
+/// A right-associative binary operator, parsed into an `Expression::InfixExpression`.
+pub const fn right_binary(token: Token, symbol: &'static str, precedence: Precedence) -> InfixOperator {
+    binary_with(token, symbol, precedence, Associativity::Right)
+}
+
+const fn binary_with(token: Token, symbol: &'static str, precedence: Precedence, associativity: Associativity) -> InfixOperator {
+    InfixOperator {
+        token,
+        symbol,
+        precedence,
+        associativity,
+        parse: Parser::parse_infix_expression,
+    }
+}
+
+/// Looks up a prefix operator by the symbol it is written with, as stored in
+/// `Expression::PrefixExpression`.
+pub fn prefix_operator(symbol: &str) -> Option<&'static PrefixOperator> {
+    PREFIX_OPERATORS.iter().find(|operator| operator.symbol == symbol)
+}
+
+/// Looks up an infix operator by the symbol it is written with, as stored in
+/// `Expression::InfixExpression`.
+pub fn infix_operator(symbol: &str) -> Option<&'static InfixOperator> {
+    INFIX_OPERATORS.iter().find(|operator| operator.symbol == symbol)
+}
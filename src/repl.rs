@@ -1,14 +1,205 @@
-use std::io;
-use crate::lexer;
+use std::fs;
+use std::{env, io};
+use std::io::{BufRead, IsTerminal, Write};
+use crate::ast::Statement;
+use crate::diagnostic::{Diagnostic, Style};
+use crate::evaluator::environment;
+use crate::evaluator::environment::Environment;
+use crate::evaluator::object::Object;
+use crate::lexer::error::LexError;
+use crate::parser::error::ParseError;
+use crate::token::Token;
+use crate::{evaluator, lexer, parser};
 
-pub fn start() {
+const PROMPT: &str = ">> ";
+
+/// The file name that diagnostics give for typed-in input.
+const INPUT_NAME: &str = "<repl>";
+
+const CONTINUATION_PROMPT: &str = ".. ";
+
+const HELP: &str = "\
+:tokens <source>  print the tokens the lexer produces for <source>
+:ast <source>     print the syntax tree the parser produces for <source>
+:env              list the current bindings
+:load <file>      evaluate a file in the current environment
+:reset            forget all bindings
+:quit             leave the REPL
+:help             show this message";
+
+enum MetaCommandOutcome {
+    Continue,
+    Quit,
+}
+
+/// Runs the read-eval-print loop on standard input and output. Like `evaluator::eval`, it needs no
+/// more than a default-sized native stack, however deeply the input nests calls.
+pub fn start() -> io::Result<()> {
     let stdin = io::stdin();
+    let stdout = io::stdout();
+    let style = match stdout.is_terminal() && env::var_os("NO_COLOR").is_none() {
+        true => Style::Ansi,
+        false => Style::Plain,
+    };
+    start_with_style(stdin.lock(), stdout.lock(), style)
+}
+
+pub fn start_with<R: BufRead, W: Write>(input: R, output: W) -> io::Result<()> {
+    start_with_style(input, output, Style::Plain)
+}
+
+/// Runs the read-eval-print loop over any line-based input until it reaches end of input or `:quit`.
+/// Bindings made with `let` persist from one input to the next. Input that is obviously unfinished,
+/// such as an unclosed `{` or a trailing operator, is continued on the next line; an empty line
+/// submits it as-is. Errors are shown as diagnostics rendered in `style`.
+pub fn start_with_style<R: BufRead, W: Write>(mut input: R, mut output: W, style: Style) -> io::Result<()> {
+    let mut environment = environment::new();
+    let mut buffer = String::new();
+
     loop {
-        let mut buffer = String::new();
-        stdin.read_line(&mut buffer).unwrap();
-        let mut lexer = lexer::new(buffer);
-        while let Ok(token) = lexer.next_token() {
-            println!("{:?}", token);
+        let prompt = match buffer.is_empty() {
+            true => PROMPT,
+            false => CONTINUATION_PROMPT,
+        };
+        write!(output, "{}", prompt)?;
+        output.flush()?;
+
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            writeln!(output)?;
+            return Ok(());
+        }
+
+        if buffer.is_empty() && line.trim_start().starts_with(':') {
+            match run_meta_command(line.trim(), &mut environment, &mut output, style)? {
+                MetaCommandOutcome::Continue => continue,
+                MetaCommandOutcome::Quit => return Ok(()),
+            }
         }
+
+        let submitted = !buffer.is_empty() && line.trim().is_empty();
+        buffer.push_str(&line);
+        if !submitted && is_incomplete(&buffer) {
+            continue;
+        }
+
+        let source = std::mem::take(&mut buffer);
+        if source.trim().is_empty() {
+            continue;
+        }
+        eval_source(INPUT_NAME, &source, &mut environment, &mut output, style)?;
+    }
+}
+
+fn run_meta_command<W: Write>(command: &str, environment: &mut Environment, output: &mut W, style: Style) -> io::Result<MetaCommandOutcome> {
+    let (name, argument) = match command.split_once(char::is_whitespace) {
+        Some((name, argument)) => (name, argument.trim()),
+        None => (command, ""),
+    };
+
+    match name {
+        ":tokens" => print_tokens(argument, output, style)?,
+        ":ast" => print_ast(argument, output, style)?,
+        ":env" => {
+            for (name, value) in environment.bindings() {
+                writeln!(output, "{} = {}", name, value)?;
+            }
+        }
+        ":load" => match fs::read_to_string(argument) {
+            Ok(source) => eval_source(argument, &source, environment, output, style)?,
+            Err(err) => writeln!(output, "could not read {}: {}", argument, err)?,
+        },
+        ":reset" => *environment = environment::new(),
+        ":quit" => return Ok(MetaCommandOutcome::Quit),
+        ":help" => writeln!(output, "{}", HELP)?,
+        _ => writeln!(output, "unknown command: {} (try :help)", name)?,
     }
-}
\ No newline at end of file
+
+    return Ok(MetaCommandOutcome::Continue);
+}
+
+fn print_tokens<W: Write>(source: &str, output: &mut W, style: Style) -> io::Result<()> {
+    let mut lexer = lexer::new(source.into());
+    loop {
+        match lexer.next_token() {
+            Ok(spanned_token) => {
+                let span = spanned_token.span;
+                writeln!(output, "{}:{} {:?}", span.line, span.column, spanned_token.token)?;
+                if spanned_token.token == Token::Eof {
+                    return Ok(());
+                }
+            }
+            Err(error) => return write!(output, "{}", Diagnostic::from(&error).render(INPUT_NAME, source, style)),
+        }
+    }
+}
+
+fn print_ast<W: Write>(source: &str, output: &mut W, style: Style) -> io::Result<()> {
+    let mut parser = parser::new(lexer::new(source.into()));
+    match parser.parse() {
+        Ok(program) => {
+            for statement in program.statements.iter() {
+                writeln!(output, "{:#?}", statement)?;
+            }
+            Ok(())
+        }
+        Err(errors) => print_parser_errors(INPUT_NAME, source, &errors, output, style),
+    }
+}
+
+fn eval_source<W: Write>(name: &str, source: &str, environment: &mut Environment, output: &mut W, style: Style) -> io::Result<()> {
+    let mut parser = parser::new(lexer::new(source.to_string()));
+    let program = match parser.parse() {
+        Ok(program) => program,
+        Err(errors) => return print_parser_errors(name, source, &errors, output, style),
+    };
+
+    let result = evaluator::eval_with_output(&program, environment, output);
+    if let Object::Error(error) = result {
+        return write!(output, "{}", Diagnostic::from(&error).render(name, source, style));
+    }
+    if let Some(Statement::Let(_)) = program.statements.last() {
+        return Ok(());
+    }
+    writeln!(output, "{}", result)
+}
+
+fn print_parser_errors<W: Write>(name: &str, source: &str, errors: &[ParseError], output: &mut W, style: Style) -> io::Result<()> {
+    for error in errors.iter() {
+        write!(output, "{}", Diagnostic::from(error).render(name, source, style))?;
+    }
+    Ok(())
+}
+
+/// Reports whether `source` looks unfinished: it has unclosed delimiters, an unterminated
+/// string or block comment, or ends with an operator that still needs a right-hand side.
+fn is_incomplete(source: &str) -> bool {
+    let mut lexer = lexer::new(source.into());
+    let mut depth: i32 = 0;
+    let mut last_token = Token::Eof;
+
+    loop {
+        let token = match lexer.next_token() {
+            Ok(spanned_token) => spanned_token.token,
+            Err(LexError::UnterminatedString { .. } | LexError::UnterminatedComment { .. }) => return true,
+            Err(_) => return false,
+        };
+
+        match token {
+            Token::LeftParenthesis | Token::LeftBracket | Token::LeftSquare => depth += 1,
+            Token::RightParenthesis | Token::RightBracket | Token::RightSquare => depth -= 1,
+            Token::Eof => break,
+            _ => {}
+        }
+        last_token = token;
+    }
+
+    let ends_with_operator = matches!(
+        last_token,
+        Token::Assign | Token::Plus | Token::Minus | Token::Asterisk | Token::Slash | Token::Bang
+            | Token::Equal | Token::NotEqual | Token::LessThan | Token::GreaterThan
+            | Token::Comma | Token::Colon
+    );
+
+    return depth > 0 || ends_with_operator;
+}
@@ -0,0 +1,173 @@
+use crate::ast::{BlockStatement, Expression, Program, Statement};
+use crate::serialize::{token_kind, token_literal, write_string, SCHEMA_VERSION};
+use crate::token::{Span, SpannedToken};
+
+/// Serializes a token stream, as produced by repeated calls to `Lexer::next_token`, to a single
+/// line of JSON.
+pub fn tokens(tokens: &[SpannedToken]) -> String {
+    let mut output = format!("{{\"version\":{},\"tokens\":[", SCHEMA_VERSION);
+    for (i, spanned_token) in tokens.iter().enumerate() {
+        if i > 0 {
+            output.push(',');
+        }
+        output.push_str("{\"kind\":");
+        write_string(&mut output, token_kind(&spanned_token.token));
+        if let Some(literal) = token_literal(&spanned_token.token) {
+            output.push_str(",\"literal\":");
+            write_string(&mut output, literal);
+        }
+        write_span(&mut output, spanned_token.span);
+        output.push('}');
+    }
+    output.push_str("]}");
+    return output;
+}
+
+/// Serializes a syntax tree, with the span of every node, to a single line of JSON.
+pub fn program(program: &Program) -> String {
+    let mut output = format!("{{\"version\":{},\"kind\":\"Program\",\"statements\":", SCHEMA_VERSION);
+    write_statements(&mut output, &program.statements);
+    output.push('}');
+    return output;
+}
+
+fn write_statements(output: &mut String, statements: &[Statement]) {
+    output.push('[');
+    for (i, statement) in statements.iter().enumerate() {
+        if i > 0 {
+            output.push(',');
+        }
+        write_statement(output, statement);
+    }
+    output.push(']');
+}
+
+fn write_statement(output: &mut String, statement: &Statement) {
+    match statement {
+        Statement::Let(let_statement) => {
+            output.push_str("{\"kind\":\"Let\",\"name\":");
+            write_string(output, &let_statement.identifier_name);
+            output.push_str(",\"value\":");
+            write_expression(output, &let_statement.value);
+        }
+        Statement::Return(return_statement) => {
+            output.push_str("{\"kind\":\"Return\",\"value\":");
+            write_expression(output, &return_statement.value);
+        }
+        Statement::Expression(expression) => {
+            output.push_str("{\"kind\":\"Expression\",\"expression\":");
+            write_expression(output, expression);
+        }
+        Statement::Error(_) => output.push_str("{\"kind\":\"Error\""),
+    }
+    write_span(output, statement.span());
+    output.push('}');
+}
+
+fn write_block(output: &mut String, block: &BlockStatement) {
+    output.push_str("{\"kind\":\"Block\",\"statements\":");
+    write_statements(output, &block.statements);
+    write_span(output, block.span);
+    output.push('}');
+}
+
+fn write_optional_block(output: &mut String, block: &Option<BlockStatement>) {
+    match block {
+        Some(block) => write_block(output, block),
+        None => output.push_str("null"),
+    }
+}
+
+fn write_expressions(output: &mut String, expressions: &[Expression]) {
+    output.push('[');
+    for (i, expression) in expressions.iter().enumerate() {
+        if i > 0 {
+            output.push(',');
+        }
+        write_expression(output, expression);
+    }
+    output.push(']');
+}
+
+fn write_expression(output: &mut String, expression: &Expression) {
+    match expression {
+        Expression::Identifier { token, .. } => {
+            output.push_str("{\"kind\":\"Identifier\",\"name\":");
+            write_string(output, token_literal(token).unwrap_or_default());
+        }
+        Expression::Boolean { value, .. } => output.push_str(&format!("{{\"kind\":\"Boolean\",\"value\":{}", value)),
+        Expression::IntegerLiteral { value, .. } => output.push_str(&format!("{{\"kind\":\"Integer\",\"value\":{}", value)),
+        Expression::StringLiteral { value, .. } => {
+            output.push_str("{\"kind\":\"String\",\"value\":");
+            write_string(output, value);
+        }
+        Expression::PrefixExpression { operator, right, .. } => {
+            output.push_str("{\"kind\":\"Prefix\",\"operator\":");
+            write_string(output, operator);
+            output.push_str(",\"right\":");
+            write_expression(output, right);
+        }
+        Expression::InfixExpression { operator, left, right, .. } => {
+            output.push_str("{\"kind\":\"Infix\",\"operator\":");
+            write_string(output, operator);
+            output.push_str(",\"left\":");
+            write_expression(output, left);
+            output.push_str(",\"right\":");
+            write_expression(output, right);
+        }
+        Expression::IfExpression { condition, consequence, alternative, .. } => {
+            output.push_str("{\"kind\":\"If\",\"condition\":");
+            write_expression(output, condition);
+            output.push_str(",\"consequence\":");
+            write_optional_block(output, consequence);
+            output.push_str(",\"alternative\":");
+            write_optional_block(output, alternative);
+        }
+        Expression::Function { parameters, body, .. } => {
+            output.push_str("{\"kind\":\"Function\",\"parameters\":");
+            write_expressions(output, parameters);
+            output.push_str(",\"body\":");
+            write_block(output, body);
+        }
+        Expression::Call { function, arguments, .. } => {
+            output.push_str("{\"kind\":\"Call\",\"function\":");
+            write_expression(output, function);
+            output.push_str(",\"arguments\":");
+            write_expressions(output, arguments);
+        }
+        Expression::ArrayLiteral { elements, .. } => {
+            output.push_str("{\"kind\":\"Array\",\"elements\":");
+            write_expressions(output, elements);
+        }
+        Expression::Index { left, index, .. } => {
+            output.push_str("{\"kind\":\"Index\",\"left\":");
+            write_expression(output, left);
+            output.push_str(",\"index\":");
+            write_expression(output, index);
+        }
+        Expression::HashLiteral { pairs, .. } => {
+            output.push_str("{\"kind\":\"Hash\",\"pairs\":[");
+            for (i, (key, value)) in pairs.iter().enumerate() {
+                if i > 0 {
+                    output.push(',');
+                }
+                output.push_str("{\"key\":");
+                write_expression(output, key);
+                output.push_str(",\"value\":");
+                write_expression(output, value);
+                output.push('}');
+            }
+            output.push(']');
+        }
+        Expression::Error { .. } => output.push_str("{\"kind\":\"Error\""),
+    }
+    write_span(output, expression.span());
+    output.push('}');
+}
+
+fn write_span(output: &mut String, span: Span) {
+    output.push_str(&format!(
+        ",\"span\":{{\"start\":{},\"end\":{},\"line\":{},\"column\":{}}}",
+        span.start, span.end, span.line, span.column
+    ));
+}
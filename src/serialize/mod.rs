@@ -0,0 +1,141 @@
+//! Machine-readable forms of the token stream and syntax tree for tools that do not link against
+//! this crate, such as editors, the web playground and test oracles.
+//!
+//! Two encodings are provided:
+//!
+//! - [`json`] carries every node with its source span and is the format to build tools on.
+//! - [`sexpr`] is a compact, span-free S-expression form meant for reading and for golden tests.
+//!
+//! # Schema
+//!
+//! The JSON schema is stable: within one `version`, fields and `kind` names are only ever added,
+//! never renamed or removed, so consumers should ignore fields they do not know. `version` is
+//! bumped for any other change.
+//!
+//! A span is `{"start": 0, "end": 3, "line": 1, "column": 1}`. `start` and `end` are byte offsets
+//! into the source and `end` is exclusive. `line` and `column` are where the span starts, both
+//! 1-based, with the column counted in characters.
+//!
+//! ## Tokens
+//!
+//! ```json
+//! {"version": 1, "tokens": [{"kind": "Let", "span": {...}}, {"kind": "Identifier", "literal": "x", "span": {...}}, ...]}
+//! ```
+//!
+//! Every token has a `kind` and a `span`. `Identifier`, `Int` and `Str` tokens also have a
+//! `literal`: the name, the digits, or the string's value after escapes are processed. The list
+//! always ends with an `Eof` token. The remaining kinds are `Illegal`, `Assign`, `Plus`, `Minus`,
+//! `Asterisk`, `Slash`, `Bang`, `Equal`, `NotEqual`, `LessThan`, `GreaterThan`, `Comma`, `Colon`,
+//! `Semicolon`, `LeftParenthesis`, `RightParenthesis`, `LeftBracket`, `RightBracket`,
+//! `LeftSquare`, `RightSquare`, `Function`, `Let`, `Return`, `If`, `Else`, `True` and `False`.
+//!
+//! ## Syntax tree
+//!
+//! ```json
+//! {"version": 1, "kind": "Program", "statements": [...]}
+//! ```
+//!
+//! Every statement and expression node is an object with a `kind`, a `span` and the fields below.
+//! `E` is an expression node, `B` is a block and `S` is a statement node.
+//!
+//! | `kind`       | fields                                                            |
+//! |--------------|-------------------------------------------------------------------|
+//! | `Let`        | `name`: string, `value`: E                                        |
+//! | `Return`     | `value`: E                                                        |
+//! | `Expression` | `expression`: E                                                   |
+//! | `Block`      | `statements`: [S]                                                 |
+//! | `Identifier` | `name`: string                                                    |
+//! | `Boolean`    | `value`: boolean                                                  |
+//! | `Integer`    | `value`: number (any 64-bit signed integer)                       |
+//! | `String`     | `value`: string                                                   |
+//! | `Prefix`     | `operator`: string, `right`: E                                    |
+//! | `Infix`      | `operator`: string, `left`: E, `right`: E                         |
+//! | `If`         | `condition`: E, `consequence`: B or null, `alternative`: B or null |
+//! | `Function`   | `parameters`: [E], `body`: B                                      |
+//! | `Call`       | `function`: E, `arguments`: [E]                                   |
+//! | `Array`      | `elements`: [E]                                                   |
+//! | `Index`      | `left`: E, `index`: E                                             |
+//! | `Hash`       | `pairs`: [{`key`: E, `value`: E}]                                 |
+//! | `Error`      | none; stands in for a statement or expression that failed to parse |
+//!
+//! Function parameters are `Identifier` nodes. A source that fails to parse is still serialized,
+//! with `Error` nodes where the parser recovered.
+//!
+//! ## S-expressions
+//!
+//! `let x = add(1, 2 * y);` becomes `(program (let x (call add 1 (* 2 y))))`. Identifiers,
+//! integers and booleans are bare atoms and strings are double-quoted with JSON escapes. Compound
+//! nodes are `(prefix-operator E)`, `(infix-operator E E)`, `(return E)`, `(block S...)`,
+//! `(if E B B)` with `nil` for a missing block, `(fn (parameter...) B)`, `(call E E...)`,
+//! `(array E...)`, `(index E E)`, `(hash (E E)...)` and `(error)`. An expression statement is
+//! just its expression. Tokens are written as `(tokens (Let) (Identifier "x") ... (Eof))`.
+
+pub mod json;
+pub mod sexpr;
+
+use crate::token::Token;
+
+/// The version of the serialized forms described in the module documentation.
+pub const SCHEMA_VERSION: u32 = 1;
+
+fn token_kind(token: &Token) -> &'static str {
+    match token {
+        Token::Illegal => "Illegal",
+        Token::Eof => "Eof",
+        Token::Identifier { .. } => "Identifier",
+        Token::Int { .. } => "Int",
+        Token::Str { .. } => "Str",
+        Token::Assign => "Assign",
+        Token::Plus => "Plus",
+        Token::Minus => "Minus",
+        Token::Comma => "Comma",
+        Token::Colon => "Colon",
+        Token::Semicolon => "Semicolon",
+        Token::LeftParenthesis => "LeftParenthesis",
+        Token::RightParenthesis => "RightParenthesis",
+        Token::LeftBracket => "LeftBracket",
+        Token::RightBracket => "RightBracket",
+        Token::LeftSquare => "LeftSquare",
+        Token::RightSquare => "RightSquare",
+        Token::Return => "Return",
+        Token::Function => "Function",
+        Token::Let => "Let",
+        Token::False => "False",
+        Token::True => "True",
+        Token::Slash => "Slash",
+        Token::Asterisk => "Asterisk",
+        Token::Bang => "Bang",
+        Token::If => "If",
+        Token::Else => "Else",
+        Token::LessThan => "LessThan",
+        Token::GreaterThan => "GreaterThan",
+        Token::Equal => "Equal",
+        Token::NotEqual => "NotEqual",
+    }
+}
+
+fn token_literal(token: &Token) -> Option<&str> {
+    match token {
+        Token::Identifier { literal } | Token::Int { literal } | Token::Str { literal } => Some(literal),
+        _ => None,
+    }
+}
+
+/// Writes `text` as a double-quoted JSON string.
+fn write_string(output: &mut String, text: &str) {
+    output.push('"');
+    for character in text.chars() {
+        match character {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            character if (character as u32) < 0x20 => {
+                output.push_str(&format!("\\u{:04x}", character as u32));
+            }
+            character => output.push(character),
+        }
+    }
+    output.push('"');
+}
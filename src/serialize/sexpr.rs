@@ -0,0 +1,149 @@
+use crate::ast::{BlockStatement, Expression, Program, Statement};
+use crate::serialize::{token_kind, token_literal, write_string};
+use crate::token::SpannedToken;
+
+/// Writes a token stream as `(tokens (Let) (Identifier "x") ...)`, without spans.
+pub fn tokens(tokens: &[SpannedToken]) -> String {
+    let mut output = String::from("(tokens");
+    for spanned_token in tokens.iter() {
+        output.push_str(" (");
+        output.push_str(token_kind(&spanned_token.token));
+        if let Some(literal) = token_literal(&spanned_token.token) {
+            output.push(' ');
+            write_string(&mut output, literal);
+        }
+        output.push(')');
+    }
+    output.push(')');
+    return output;
+}
+
+/// Writes a syntax tree as a single S-expression, without spans.
+pub fn program(program: &Program) -> String {
+    let mut output = String::from("(program");
+    write_statements(&mut output, &program.statements);
+    output.push(')');
+    return output;
+}
+
+fn write_statements(output: &mut String, statements: &[Statement]) {
+    for statement in statements.iter() {
+        output.push(' ');
+        write_statement(output, statement);
+    }
+}
+
+fn write_statement(output: &mut String, statement: &Statement) {
+    match statement {
+        Statement::Let(let_statement) => {
+            output.push_str("(let ");
+            output.push_str(&let_statement.identifier_name);
+            output.push(' ');
+            write_expression(output, &let_statement.value);
+            output.push(')');
+        }
+        Statement::Return(return_statement) => {
+            output.push_str("(return ");
+            write_expression(output, &return_statement.value);
+            output.push(')');
+        }
+        Statement::Expression(expression) => write_expression(output, expression),
+        Statement::Error(_) => output.push_str("(error)"),
+    }
+}
+
+fn write_block(output: &mut String, block: &BlockStatement) {
+    output.push_str("(block");
+    write_statements(output, &block.statements);
+    output.push(')');
+}
+
+fn write_optional_block(output: &mut String, block: &Option<BlockStatement>) {
+    match block {
+        Some(block) => write_block(output, block),
+        None => output.push_str("nil"),
+    }
+}
+
+fn write_expressions(output: &mut String, expressions: &[Expression]) {
+    for expression in expressions.iter() {
+        output.push(' ');
+        write_expression(output, expression);
+    }
+}
+
+fn write_expression(output: &mut String, expression: &Expression) {
+    match expression {
+        Expression::Identifier { token, .. } => output.push_str(token_literal(token).unwrap_or_default()),
+        Expression::Boolean { value, .. } => output.push_str(&value.to_string()),
+        Expression::IntegerLiteral { value, .. } => output.push_str(&value.to_string()),
+        Expression::StringLiteral { value, .. } => write_string(output, value),
+        Expression::PrefixExpression { operator, right, .. } => {
+            output.push('(');
+            output.push_str(operator);
+            output.push(' ');
+            write_expression(output, right);
+            output.push(')');
+        }
+        Expression::InfixExpression { operator, left, right, .. } => {
+            output.push('(');
+            output.push_str(operator);
+            output.push(' ');
+            write_expression(output, left);
+            output.push(' ');
+            write_expression(output, right);
+            output.push(')');
+        }
+        Expression::IfExpression { condition, consequence, alternative, .. } => {
+            output.push_str("(if ");
+            write_expression(output, condition);
+            output.push(' ');
+            write_optional_block(output, consequence);
+            output.push(' ');
+            write_optional_block(output, alternative);
+            output.push(')');
+        }
+        Expression::Function { parameters, body, .. } => {
+            output.push_str("(fn (");
+            for (i, parameter) in parameters.iter().enumerate() {
+                if i > 0 {
+                    output.push(' ');
+                }
+                write_expression(output, parameter);
+            }
+            output.push_str(") ");
+            write_block(output, body);
+            output.push(')');
+        }
+        Expression::Call { function, arguments, .. } => {
+            output.push_str("(call ");
+            write_expression(output, function);
+            write_expressions(output, arguments);
+            output.push(')');
+        }
+        Expression::ArrayLiteral { elements, .. } => {
+            output.push_str("(array");
+            write_expressions(output, elements);
+            output.push(')');
+        }
+        Expression::Index { left, index, .. } => {
+            output.push_str("(index ");
+            write_expression(output, left);
+            output.push(' ');
+            write_expression(output, index);
+            output.push(')');
+        }
+        Expression::HashLiteral { pairs, .. } => {
+            output.push_str("(hash");
+            for (key, value) in pairs.iter() {
+                output.push_str(" (");
+                write_expression(output, key);
+                output.push(' ');
+                write_expression(output, value);
+                output.push(')');
+            }
+            output.push(')');
+        }
+        Expression::Error { .. } => output.push_str("(error)"),
+    }
+}
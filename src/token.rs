@@ -1,5 +1,48 @@
-use crate::parser::Precedence;
-use crate::token::token_value::{BOOL, FUNCTION, IF, LEFT_PARENTHESIS};
+use std::fmt::{Display, Formatter};
+
+#[derive(PartialEq, Debug, Clone, Copy, Eq, Hash, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Span {
+    /// Joins two spans into one that starts where `self` starts and ends where `other` ends.
+    pub fn to(&self, other: Span) -> Span {
+        Span {
+            start: self.start,
+            end: other.end,
+            line: self.line,
+            column: self.column,
+        }
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Copy, Eq, Hash)]
+pub enum TriviaKind {
+    LineComment,
+    BlockComment,
+}
+
+/// Source text that carries no meaning for the parser, such as a comment. `text` is the
+/// comment exactly as written, delimiters included.
+#[derive(PartialEq, Debug, Clone, Eq, Hash)]
+pub struct Trivia {
+    pub kind: TriviaKind,
+    pub text: String,
+    pub span: Span,
+}
+
+#[derive(PartialEq, Debug, Clone, Eq, Hash)]
+pub struct SpannedToken {
+    pub token: Token,
+    pub span: Span,
+    /// Comments found between the previous token and this one. Only filled in when the lexer
+    /// was created with `LexerOptions::preserve_comments`.
+    pub leading_trivia: Vec<Trivia>,
+}
 
 #[derive(PartialEq, Debug, Clone, Eq, Hash)]
 pub enum Token {
@@ -7,15 +50,19 @@ pub enum Token {
     Eof,
     Identifier { literal: String },
     Int { literal: String },
+    Str { literal: String },
     Assign,
     Plus,
     Minus,
     Comma,
+    Colon,
     Semicolon,
     LeftParenthesis,
     RightParenthesis,
     LeftBracket,
     RightBracket,
+    LeftSquare,
+    RightSquare,
     Return,
     Function,
     Let,
@@ -32,76 +79,63 @@ pub enum Token {
     NotEqual,
 }
 
-pub mod token_value {
-    pub const IDENTIFIER: i8 = 0;
-    pub const INT: i8 = 1;
-    pub const PLUS: i8 = 2;
-    pub const MINUS: i8 = 3;
-    pub const ASTERISK: i8 = 4;
-    pub const SLASH: i8 = 5;
-    pub const BANG: i8 = 6;
-    pub const EQUAL: i8 = 7;
-    pub const NOT_EQUAL: i8 = 8;
-    pub const LESS_THAN: i8 = 9;
-    pub const GREATER_THAN: i8 = 10;
-    pub const BOOL: i8 = 11;
-    pub const LEFT_PARENTHESIS: i8 = 12;
-    pub const IF: i8 = 13;
-    pub const FUNCTION: i8 = 14;
-    pub const ILLEGAL: i8 = -1;
-}
+/// How parse errors name the tokens they expected, as they are written in source.
+pub mod token_symbol {
+    pub const IDENTIFIER: &str = "identifier";
 
-pub mod token_name {
-    pub const IDENTIFIER: &str = "Identifier";
+    pub const ASSIGN: &str = "`=`";
 
-    pub const ASSIGN: &str = "Assign";
-}
+    pub const LEFT_PARENTHESIS: &str = "`(`";
 
-impl Token {
-    pub fn value(&mut self) -> i8 {
-        match self {
-            Token::Identifier { .. } => token_value::IDENTIFIER,
-            Token::Int { .. } => token_value::INT,
-            Token::Plus => token_value::PLUS,
-            Token::Minus => token_value::MINUS,
-            Token::Asterisk => token_value::ASTERISK,
-            Token::Slash => token_value::SLASH,
-            Token::Bang => token_value::BANG,
-            Token::Equal => token_value::EQUAL,
-            Token::NotEqual => token_value::NOT_EQUAL,
-            Token::LessThan => token_value::LESS_THAN,
-            Token::GreaterThan => token_value::GREATER_THAN,
-            Token::True | Token::False => BOOL,
-            Token::LeftParenthesis => LEFT_PARENTHESIS,
-            Token::If => IF,
-            Token::Function => FUNCTION,
-            _ => token_value::ILLEGAL,
-        }
-    }
+    pub const RIGHT_PARENTHESIS: &str = "`)`";
 
-    pub fn literal(&mut self) -> Option<String> {
-        match self {
-            Token::Bang => Some("!".into()),
-            Token::Minus => Some("-".into()),
-            Token::Plus => Some("+".into()),
-            Token::Asterisk => Some("*".into()),
-            Token::Slash => Some("/".into()),
-            Token::LessThan => Some("<".into()),
-            Token::GreaterThan => Some(">".into()),
-            Token::Assign => Some("=".into()),
-            Token::Equal => Some("==".into()),
-            Token::NotEqual => Some("!=".into()),
-            _ => None
-        }
-    }
+    pub const LEFT_BRACKET: &str = "`{`";
+
+    pub const COLON: &str = "`:`";
+
+    pub const RIGHT_SQUARE: &str = "`]`";
+
+    pub const COMMA_OR_RIGHT_PARENTHESIS: &str = "`,` or `)`";
+
+    pub const COMMA_OR_RIGHT_BRACKET: &str = "`,` or `}`";
+
+    pub const COMMA_OR_RIGHT_SQUARE: &str = "`,` or `]`";
+}
 
-    pub fn precedence(&mut self) -> Option<Precedence> {
-        return match self {
-            Token::Equal | Token::NotEqual => Some(Precedence::Equals),
-            Token::LessThan | Token::GreaterThan => Some(Precedence::LessGreater),
-            Token::Plus | Token::Minus => Some(Precedence::Sum),
-            Token::Slash | Token::Asterisk => Some(Precedence::Prefix),
-            _ => None
+impl Display for Token {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            Token::Illegal => "illegal token",
+            Token::Eof => "end of input",
+            Token::Identifier { literal } | Token::Int { literal } => literal,
+            Token::Str { literal } => return write!(f, "{:?}", literal),
+            Token::Assign => "=",
+            Token::Plus => "+",
+            Token::Minus => "-",
+            Token::Comma => ",",
+            Token::Colon => ":",
+            Token::Semicolon => ";",
+            Token::LeftParenthesis => "(",
+            Token::RightParenthesis => ")",
+            Token::LeftBracket => "{",
+            Token::RightBracket => "}",
+            Token::LeftSquare => "[",
+            Token::RightSquare => "]",
+            Token::Return => "return",
+            Token::Function => "fn",
+            Token::Let => "let",
+            Token::False => "false",
+            Token::True => "true",
+            Token::Slash => "/",
+            Token::Asterisk => "*",
+            Token::Bang => "!",
+            Token::If => "if",
+            Token::Else => "else",
+            Token::LessThan => "<",
+            Token::GreaterThan => ">",
+            Token::Equal => "==",
+            Token::NotEqual => "!=",
         };
+        write!(f, "{}", text)
     }
 }
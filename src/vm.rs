@@ -0,0 +1,372 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::rc::Rc;
+use crate::compiler::code::{self, Opcode};
+use crate::compiler::symbol_table::SymbolScope;
+use crate::compiler::{Bytecode, CompiledFunction, Constant};
+use crate::evaluator;
+use crate::evaluator::builtins::{Builtin, BUILTINS};
+use crate::evaluator::error::RuntimeError;
+use crate::evaluator::object::{Capture, Closure, Locals, Object};
+
+/// How deeply calls may nest before the VM, or the evaluator, gives up with a stack overflow.
+pub const MAX_FRAMES: usize = 10_000;
+
+struct Frame {
+    closure: Rc<Closure>,
+    ip: usize,
+    /// Where the frame's part of the stack starts. The callee sits just below.
+    base_pointer: usize,
+    /// The function's local variables, parameters first. They live outside the stack so that
+    /// closures created by the call can share them.
+    locals: Locals,
+}
+
+/// Runs compiled programs. Values are the evaluator's `Object`s and the operators, indexing and
+/// builtins share the evaluator's implementation, so that both give the same results.
+pub struct Vm<'a> {
+    constants: Vec<Object>,
+    functions: Vec<Rc<CompiledFunction>>,
+    globals: Vec<Option<Object>>,
+    global_names: Vec<String>,
+    stack: Vec<Object>,
+    frames: Vec<Frame>,
+    /// Where `puts` writes.
+    output: &'a mut dyn Write,
+}
+
+pub fn new<'a>(bytecode: &Bytecode, output: &'a mut dyn Write) -> Vm<'a> {
+    let constants = bytecode.constants.iter().map(|constant| match constant {
+        Constant::Integer(value) => Object::Integer(*value),
+        Constant::Str(value) => Object::Str(value.clone()),
+    });
+    let main = Closure { function: Rc::clone(&bytecode.main), free: Vec::new() };
+
+    Vm {
+        constants: constants.collect(),
+        functions: bytecode.functions.clone(),
+        globals: vec![None; bytecode.globals.len()],
+        global_names: bytecode.globals.clone(),
+        stack: Vec::new(),
+        frames: vec![Frame { closure: Rc::new(main), ip: 0, base_pointer: 0, locals: Locals::default() }],
+        output,
+    }
+}
+
+/// Runs `bytecode` on a fresh VM and returns the value of the program, printing the output of
+/// `puts` to standard output.
+pub fn run(bytecode: &Bytecode) -> Result<Object, RuntimeError> {
+    run_with_output(bytecode, &mut io::stdout())
+}
+
+pub fn run_with_output(bytecode: &Bytecode, output: &mut dyn Write) -> Result<Object, RuntimeError> {
+    new(bytecode, output).run()
+}
+
+impl Vm<'_> {
+    /// Runs the program to completion. A runtime error carries the span of the source the failing
+    /// instruction was compiled from.
+    pub fn run(&mut self) -> Result<Object, RuntimeError> {
+        loop {
+            let frame = self.frames.last_mut().expect("the VM stops when the last frame returns");
+            let function = &frame.closure.function;
+            let start = frame.ip;
+            let opcode = match function.instructions.get(start).and_then(|byte| Opcode::from_byte(*byte)) {
+                Some(opcode) => opcode,
+                None => return Err(RuntimeError::from(format!("invalid instruction at offset {}", start))),
+            };
+            let (operands, width) = match code::read_operands(opcode, &function.instructions[start + 1..]) {
+                Some(decoded) => decoded,
+                None => return Err(RuntimeError::from(format!("truncated instruction at offset {}", start))),
+            };
+            frame.ip = start + 1 + width;
+
+            match self.execute(opcode, &operands) {
+                Ok(Some(result)) => return Ok(result),
+                Ok(None) => {}
+                Err(error) => {
+                    let frame = self.frames.last().expect("errors are raised inside a frame");
+                    return Err(match frame.closure.function.span_at(start) {
+                        Some(span) => error.at(span),
+                        None => error,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Executes one instruction, returning the value of the program once the top level returns.
+    fn execute(&mut self, opcode: Opcode, operands: &[usize]) -> Result<Option<Object>, RuntimeError> {
+        match opcode {
+            Opcode::Constant => {
+                let constant = self.constants.get(operands[0]).ok_or_else(|| invalid_operand(opcode))?;
+                self.push(constant.clone());
+            }
+            Opcode::Pop => {
+                self.pop()?;
+            }
+            Opcode::Add
+            | Opcode::Subtract
+            | Opcode::Multiply
+            | Opcode::Divide
+            | Opcode::Equal
+            | Opcode::NotEqual
+            | Opcode::LessThan
+            | Opcode::GreaterThan => {
+                let right = self.pop()?;
+                let left = self.pop()?;
+                let result = evaluator::eval_infix_expression(infix_operator(opcode), left, right);
+                self.push_result(result)?;
+            }
+            Opcode::Negate | Opcode::Not | Opcode::Positive => {
+                let operator = match opcode {
+                    Opcode::Negate => "-",
+                    Opcode::Not => "!",
+                    _ => "+",
+                };
+                let right = self.pop()?;
+                self.push_result(evaluator::eval_prefix_expression(operator, right))?;
+            }
+            Opcode::True => self.push(Object::Boolean(true)),
+            Opcode::False => self.push(Object::Boolean(false)),
+            Opcode::Null => self.push(Object::Null),
+            Opcode::Jump => self.current_frame().ip = operands[0],
+            Opcode::JumpNotTruthy => {
+                if !self.pop()?.is_truthy() {
+                    self.current_frame().ip = operands[0];
+                }
+            }
+            Opcode::GetGlobal => {
+                let value = self.globals.get(operands[0]).ok_or_else(|| invalid_operand(opcode))?.clone();
+                let value = unset_fallback(value, &self.global_names[operands[0]])?;
+                self.push(value);
+            }
+            Opcode::SetGlobal => {
+                let value = self.pop()?;
+                *self.globals.get_mut(operands[0]).ok_or_else(|| invalid_operand(opcode))? = Some(value);
+            }
+            Opcode::GetLocal => {
+                let frame = self.current_frame();
+                let name = frame.closure.function.locals.get(operands[0]).ok_or_else(|| invalid_operand(opcode))?;
+                if operands[0] >= frame.locals.borrow().len() {
+                    return Err(invalid_operand(opcode));
+                }
+                let value = unset_fallback(read_local(&frame.locals, operands[0]), name)?;
+                self.push(value);
+            }
+            Opcode::SetLocal => {
+                let value = self.pop()?;
+                let frame = self.current_frame();
+                if operands[0] >= frame.locals.borrow().len() {
+                    return Err(invalid_operand(opcode));
+                }
+                write_local(&frame.locals, operands[0], value);
+            }
+            Opcode::GetFree => {
+                let closure = &self.current_frame().closure;
+                let symbol = closure.function.free.get(operands[0]).ok_or_else(|| invalid_operand(opcode))?;
+                // The running closure was read from a variable, so its captures are all shared.
+                let value = match closure.free.get(operands[0]).ok_or_else(|| invalid_operand(opcode))? {
+                    Capture::Shared(locals, slot) => read_local(locals, *slot),
+                    Capture::Own(_) => None,
+                };
+                let value = unset_fallback(value, &symbol.name)?;
+                self.push(value);
+            }
+            Opcode::GetBuiltin => {
+                let builtin = BUILTINS.get(operands[0]).ok_or_else(|| invalid_operand(opcode))?;
+                self.push(Object::Builtin(*builtin));
+            }
+            Opcode::Array => {
+                let elements = self.pop_n(operands[0])?;
+                self.push(Object::Array(elements));
+            }
+            Opcode::CheckHashKey => {
+                let key = self.pop()?;
+                if key.hash_key().is_none() {
+                    return Err(unusable_as_hash_key(&key));
+                }
+                self.push(key);
+            }
+            Opcode::Hash => {
+                let mut hash = BTreeMap::new();
+                let mut items = self.pop_n(operands[0])?.into_iter();
+                while let (Some(key), Some(value)) = (items.next(), items.next()) {
+                    let hash_key = key.hash_key().ok_or_else(|| unusable_as_hash_key(&key))?;
+                    hash.insert(hash_key, value);
+                }
+                self.push(Object::Hash(hash));
+            }
+            Opcode::Index => {
+                let index = self.pop()?;
+                let left = self.pop()?;
+                self.push_result(evaluator::eval_index_expression(left, index))?;
+            }
+            Opcode::Call => self.call(operands[0])?,
+            Opcode::Return => {
+                let value = self.pop()?;
+                let frame = self.frames.pop().expect("returns happen inside a frame");
+                if self.frames.is_empty() {
+                    return Ok(Some(value));
+                }
+                self.stack.truncate(frame.base_pointer - 1);
+                self.push(value);
+            }
+            Opcode::Closure => {
+                let function = Rc::clone(self.functions.get(operands[0]).ok_or_else(|| invalid_operand(opcode))?);
+                let frame = self.current_frame();
+                let free = function.free.iter().map(|symbol| match symbol.scope {
+                    SymbolScope::Local if symbol.index < frame.locals.borrow().len() => {
+                        Some(Capture::Shared(Rc::clone(&frame.locals), symbol.index))
+                    }
+                    SymbolScope::Free => frame.closure.free.get(symbol.index).cloned(),
+                    _ => None,
+                });
+                let free = free.collect::<Option<_>>().ok_or_else(|| invalid_operand(opcode))?;
+                self.push(Object::Closure(Rc::new(Closure { function, free })));
+            }
+        }
+        return Ok(None);
+    }
+
+    /// Calls the function below the top `argument_count` values. A closure gets a new frame whose
+    /// locals start with the arguments and leave the rest unset, and a builtin is applied right
+    /// away.
+    fn call(&mut self, argument_count: usize) -> Result<(), RuntimeError> {
+        let callee_slot = match self.stack.len().checked_sub(argument_count + 1) {
+            Some(slot) if slot >= self.current_frame().base_pointer => slot,
+            _ => return Err(stack_underflow()),
+        };
+        match self.stack[callee_slot].clone() {
+            Object::Closure(closure) => {
+                let function = &closure.function;
+                if function.parameters.len() != argument_count {
+                    return Err(RuntimeError::from(format!(
+                        "wrong number of arguments: expected {}, got {}", function.parameters.len(), argument_count
+                    )));
+                }
+                if self.frames.len() > MAX_FRAMES {
+                    return Err(RuntimeError::from(format!("stack overflow: more than {} nested calls", MAX_FRAMES)));
+                }
+
+                let mut locals: Vec<Option<Object>> = self.pop_n(argument_count)?.into_iter().map(Some).collect();
+                locals.resize(function.locals.len().max(argument_count), None);
+                let locals = Rc::new(RefCell::new(locals));
+                let frame = Frame { closure: Rc::clone(&closure), ip: 0, base_pointer: callee_slot + 1, locals };
+                self.frames.push(frame);
+                Ok(())
+            }
+            Object::Builtin(builtin) => {
+                let arguments = self.pop_n(argument_count)?;
+                self.pop()?;
+                let result = builtin.call(arguments, self.output);
+                self.push_result(result)
+            }
+            callee => Err(RuntimeError::from(format!("not a function: {}", callee.type_name()))),
+        }
+    }
+
+    fn current_frame(&mut self) -> &mut Frame {
+        self.frames.last_mut().expect("the VM stops when the last frame returns")
+    }
+
+    fn push(&mut self, value: Object) {
+        self.stack.push(value);
+    }
+
+    /// Pushes the result of an operation, or raises it if it is an error.
+    fn push_result(&mut self, result: Object) -> Result<(), RuntimeError> {
+        match result {
+            Object::Error(error) => Err(error),
+            result => {
+                self.push(result);
+                Ok(())
+            }
+        }
+    }
+
+    /// Pops the top value of the current frame's part of the stack.
+    fn pop(&mut self) -> Result<Object, RuntimeError> {
+        if self.stack.len() <= self.current_frame().base_pointer {
+            return Err(stack_underflow());
+        }
+        self.stack.pop().ok_or_else(stack_underflow)
+    }
+
+    /// Pops the top `count` values of the current frame's part of the stack, the deepest first.
+    fn pop_n(&mut self, count: usize) -> Result<Vec<Object>, RuntimeError> {
+        match self.stack.len().checked_sub(count) {
+            Some(start) if start >= self.current_frame().base_pointer => Ok(self.stack.split_off(start)),
+            _ => Err(stack_underflow()),
+        }
+    }
+}
+
+fn unusable_as_hash_key(key: &Object) -> RuntimeError {
+    RuntimeError::from(format!("unusable as hash key: {}", key.type_name()))
+}
+
+/// The error for an instruction that pops more values than its frame pushed, which the compiler
+/// never emits.
+fn stack_underflow() -> RuntimeError {
+    RuntimeError::from("stack underflow")
+}
+
+/// The error for an operand that refers to nothing, which the compiler never emits.
+fn invalid_operand(opcode: Opcode) -> RuntimeError {
+    RuntimeError::from(format!("invalid operand for {}", opcode.name()))
+}
+
+/// The value in a slot of `locals`. A closure stored there that captures its own locals gets them
+/// back.
+fn read_local(locals: &Locals, slot: usize) -> Option<Object> {
+    match locals.borrow()[slot].clone()? {
+        Object::Closure(closure) if closure.free.iter().any(|capture| matches!(capture, Capture::Own(_))) => {
+            let free = closure.free.iter().map(|capture| match capture {
+                Capture::Own(slot) => Capture::Shared(Rc::clone(locals), *slot),
+                capture => capture.clone(),
+            });
+            Some(Object::Closure(Rc::new(Closure { function: Rc::clone(&closure.function), free: free.collect() })))
+        }
+        value => Some(value),
+    }
+}
+
+/// Stores `value` in a slot of `locals`. A closure that captures slots of these locals refers to
+/// them as its own instead, so that it and the locals do not keep each other alive.
+fn write_local(locals: &Locals, slot: usize, value: Object) {
+    let is_own = |capture: &Capture| matches!(capture, Capture::Shared(shared, _) if Rc::ptr_eq(shared, locals));
+    let value = match value {
+        Object::Closure(closure) if closure.free.iter().any(is_own) => {
+            let free = closure.free.iter().map(|capture| match capture {
+                Capture::Shared(_, slot) if is_own(capture) => Capture::Own(*slot),
+                capture => capture.clone(),
+            });
+            Object::Closure(Rc::new(Closure { function: Rc::clone(&closure.function), free: free.collect() }))
+        }
+        value => value,
+    };
+    locals.borrow_mut()[slot] = Some(value);
+}
+
+/// The value of a variable, or for one whose `let` has not run yet the builtin of the same name,
+/// the same as the evaluator finds.
+fn unset_fallback(value: Option<Object>, name: &str) -> Result<Object, RuntimeError> {
+    value
+        .or_else(|| Builtin::lookup(name).map(Object::Builtin))
+        .ok_or_else(|| RuntimeError::from(format!("identifier not found: {}", name)))
+}
+
+fn infix_operator(opcode: Opcode) -> &'static str {
+    match opcode {
+        Opcode::Add => "+",
+        Opcode::Subtract => "-",
+        Opcode::Multiply => "*",
+        Opcode::Divide => "/",
+        Opcode::Equal => "==",
+        Opcode::NotEqual => "!=",
+        Opcode::LessThan => "<",
+        _ => ">",
+    }
+}
@@ -0,0 +1,149 @@
+use tarzan::{compiler, lexer, parser, vm};
+use std::rc::Rc;
+use tarzan::compiler::Bytecode;
+use tarzan::compiler::code::{self, Opcode};
+use tarzan::compiler::symbol_table::{Symbol, SymbolScope};
+use tarzan::compiler::error::LoadError;
+use tarzan::compiler::file::{self, FORMAT_VERSION, MAGIC};
+use tarzan::evaluator::object::Object;
+
+fn compile(source_code: &str) -> Bytecode {
+    let mut parser = parser::new(lexer::new(source_code.into()));
+    let program = parser.parse().unwrap_or_else(|errors| panic!("found parser errors: {:?}", errors));
+    compiler::compile(&program).unwrap_or_else(|error| panic!("found compile error: {}", error))
+}
+
+/// Recomputes the checksum after `bytes` were edited, so that the edit itself is what gets checked.
+fn reseal(bytes: &mut Vec<u8>) {
+    bytes.truncate(bytes.len() - 4);
+    let checksum = file::checksum(bytes);
+    bytes.extend_from_slice(&checksum.to_be_bytes());
+}
+
+#[test]
+fn test_write_and_read() {
+    let bytecode = compile("
+        let greeting = \"héllo\";
+        let adder = fn(x) { fn(y) { x + y } };
+        let f = fn(n) { if (n < 2) { 1 } else { n * f(n - 1) } };
+        [len(greeting), adder(-1)(3), f(5)]");
+    let bytes = file::write(&bytecode);
+    assert!(bytes.starts_with(MAGIC));
+    assert_eq!(FORMAT_VERSION.to_be_bytes(), bytes[4..6]);
+
+    let loaded = file::read(&bytes).unwrap();
+    assert_eq!(bytecode, loaded);
+    let result = vm::run(&loaded).unwrap();
+    assert_eq!(Object::Array(vec![Object::Integer(5), Object::Integer(2), Object::Integer(120)]), result);
+}
+
+#[test]
+fn test_read_errors() {
+    let bytes = file::write(&compile("let x = 1; x + 2"));
+
+    assert_eq!(0xCBF4_3926, file::checksum(b"123456789"));
+    assert_eq!(Err(LoadError::NotBytecode), file::read(b"let x = 1;"));
+
+    let mut other_version = bytes.clone();
+    other_version[4..6].copy_from_slice(&(FORMAT_VERSION + 1).to_be_bytes());
+    let error = file::read(&other_version).unwrap_err();
+    assert_eq!(LoadError::UnsupportedVersion { found: FORMAT_VERSION + 1, expected: FORMAT_VERSION }, error);
+    assert_eq!(
+        format!(
+            "bytecode format version {} is not supported, expected version {}; rebuild the script with `tarzan build`",
+            FORMAT_VERSION + 1,
+            FORMAT_VERSION
+        ),
+        error.to_string()
+    );
+
+    let mut corrupt = bytes.clone();
+    corrupt[10] ^= 0xff;
+    assert_eq!(Err(LoadError::ChecksumMismatch), file::read(&corrupt));
+
+    assert_eq!(Err(LoadError::Truncated), file::read(&bytes[..5]));
+    let mut truncated = bytes[..bytes.len() - 10].to_vec();
+    reseal(&mut truncated);
+    assert_eq!(Err(LoadError::Truncated), file::read(&truncated));
+
+    let mut trailing = bytes.clone();
+    trailing.insert(trailing.len() - 4, 0);
+    reseal(&mut trailing);
+    assert_eq!(Err(LoadError::Malformed("unexpected data after the program".into())), file::read(&trailing));
+}
+
+#[test]
+fn test_read_rejects_out_of_range_operands() {
+    let bytecode = compile("1");
+    let bytes = file::write(&bytecode);
+    // The program is `Constant 0; Return`, followed by its line table and the checksum.
+    let instructions = bytes.len() - 4 - 4 - 20 - 4;
+    assert_eq!(0, bytes[instructions]);
+
+    let mut out_of_range = bytes.clone();
+    out_of_range[instructions + 2] = 1;
+    reseal(&mut out_of_range);
+    assert_eq!(
+        Err(LoadError::Malformed("operand out of range for Constant at offset 0".into())),
+        file::read(&out_of_range)
+    );
+}
+
+#[test]
+fn test_run_rejects_unbalanced_stack() {
+    let bytecode = compile("let f = fn(x) { x }; f(1)");
+    let mut bytes = file::write(&bytecode);
+    // Reading does not follow what is on the stack, so `Call 9` loads, but running it must fail.
+    let main = &bytecode.main;
+    let instructions = bytes.len() - 4 - 4 - 20 * main.spans.len() - main.instructions.len();
+    let call = main.instructions.windows(2).position(|pair| pair == [Opcode::Call as u8, 1]).unwrap();
+    bytes[instructions + call + 1] = 9;
+    reseal(&mut bytes);
+
+    let loaded = file::read(&bytes).unwrap();
+    let error = vm::run(&loaded).unwrap_err();
+    assert_eq!("stack underflow", error.message);
+    assert_eq!(Some((1, 22)), error.span.map(|span| (span.line, span.column)));
+}
+
+/// A program whose main function runs `instructions` after being given `locals` and `free`, which
+/// the compiler never gives it.
+fn main_with(locals: &[&str], free: Vec<Symbol>, instructions: Vec<Vec<u8>>) -> Bytecode {
+    let mut bytecode = compile("1");
+    let mut main = (*bytecode.main).clone();
+    main.locals = locals.iter().map(|name| name.to_string()).collect();
+    main.free = free;
+    main.instructions = instructions.concat();
+    main.spans = vec![(0, main.spans[0].1)];
+    bytecode.main = Rc::new(main);
+    return bytecode;
+}
+
+#[test]
+fn test_main_without_frame_variables() {
+    let free = || vec![Symbol { name: "x".into(), scope: SymbolScope::Local, index: 0 }];
+    let programs = [
+        main_with(&["x"], Vec::new(), vec![code::make(Opcode::GetLocal, &[0]), code::make(Opcode::Return, &[])]),
+        main_with(
+            &["x"],
+            Vec::new(),
+            vec![
+                code::make(Opcode::Constant, &[0]),
+                code::make(Opcode::SetLocal, &[0]),
+                code::make(Opcode::Constant, &[0]),
+                code::make(Opcode::Return, &[]),
+            ],
+        ),
+        main_with(&[], free(), vec![code::make(Opcode::GetFree, &[0]), code::make(Opcode::Return, &[])]),
+    ];
+
+    for bytecode in programs {
+        // The loader rejects the file, and the VM fails instead of panicking if it runs anyway.
+        assert_eq!(
+            Err(LoadError::Malformed("the main function has parameters, locals or free variables".into())),
+            file::read(&file::write(&bytecode))
+        );
+        let error = vm::run(&bytecode).unwrap_err();
+        assert!(error.message.starts_with("invalid operand for "), "{}", error.message);
+    }
+}
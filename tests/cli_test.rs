@@ -0,0 +1,370 @@
+use std::fs;
+use std::process::Command;
+use tarzan::cli;
+use tarzan::cli::exit_code;
+use tarzan::diagnostic::Style;
+
+struct CliOutput {
+    code: i32,
+    stdout: String,
+    stderr: String,
+}
+
+fn run_cli(args: &[&str], stdin: &str) -> CliOutput {
+    let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+    let mut stdout = Vec::new();
+    let mut stderr = Vec::new();
+    let code = cli::run(&args, stdin.as_bytes(), &mut stdout, &mut stderr, Style::Plain);
+    return CliOutput {
+        code,
+        stdout: String::from_utf8(stdout).unwrap(),
+        stderr: String::from_utf8(stderr).unwrap(),
+    };
+}
+
+fn write_script(name: &str, source: &str) -> String {
+    let path = std::env::temp_dir().join(format!("tarzan_cli_test_{}_{}.mk", std::process::id(), name));
+    fs::write(&path, source).unwrap();
+    return path.to_str().unwrap().to_string();
+}
+
+#[test]
+fn test_run_prints_final_value() {
+    let path = write_script("run", "let add = fn(a, b) { a + b };\nadd(40, 2)\n");
+    let output = run_cli(&["run", &path], "");
+    assert_eq!(exit_code::SUCCESS, output.code);
+    assert_eq!("42\n", output.stdout);
+    assert_eq!("", output.stderr);
+}
+
+#[test]
+fn test_run_reads_stdin() {
+    let output = run_cli(&["run", "-"], "\"a\" + \"b\"");
+    assert_eq!(exit_code::SUCCESS, output.code);
+    assert_eq!("ab\n", output.stdout);
+}
+
+#[test]
+fn test_run_prints_nothing_for_null() {
+    let output = run_cli(&["run", "-"], "let x = 1;");
+    assert_eq!(exit_code::SUCCESS, output.code);
+    assert_eq!("", output.stdout);
+}
+
+#[test]
+fn test_run_writes_puts_to_stdout() {
+    let output = run_cli(&["run", "-"], "puts(\"a\", 1); puts([2]); 3");
+    assert_eq!(exit_code::SUCCESS, output.code);
+    assert_eq!("a\n1\n[2]\n3\n", output.stdout);
+
+    let path = write_script("puts", "puts(1);\n2");
+    let output_path = path.replace(".mk", ".mkc");
+    let output = run_cli(&["build", &path, "-o", &output_path], "");
+    assert_eq!(exit_code::SUCCESS, output.code);
+    let output = run_cli(&["run", &output_path], "");
+    assert_eq!(exit_code::SUCCESS, output.code);
+    assert_eq!("1\n2\n", output.stdout);
+}
+
+#[test]
+fn test_run_optimizes_and_warns() {
+    let output = run_cli(&["run", "-"], "if (1 > 2) { 1 / 0 } else { 2 * 3 }");
+    assert_eq!(exit_code::SUCCESS, output.code);
+    assert_eq!("6\n", output.stdout);
+    assert_eq!("", output.stderr);
+
+    let output = run_cli(&["run", "-"], "let f = fn(x) { x / 0 };\n1");
+    assert_eq!(exit_code::SUCCESS, output.code);
+    assert_eq!("1\n", output.stdout);
+    assert!(output.stderr.starts_with("warning[W0001]: division by zero\n --> <stdin>:1:17\n"), "{}", output.stderr);
+}
+
+#[test]
+fn test_run_fails_on_runtime_error() {
+    let output = run_cli(&["run", "-"], "1 + true");
+    assert_eq!(exit_code::FAILURE, output.code);
+    assert_eq!("", output.stdout);
+    assert_eq!("\
+error[E0201]: type mismatch: INTEGER + BOOLEAN
+ --> <stdin>:1:1
+  |
+1 | 1 + true
+  | ^^^^^^^^
+", output.stderr);
+}
+
+#[test]
+fn test_run_fails_on_parse_error() {
+    let path = write_script("parse_error", "let x 5;");
+    let output = run_cli(&["run", &path], "");
+    assert_eq!(exit_code::FAILURE, output.code);
+    assert!(output.stderr.starts_with(&format!("error[E0101]: expected `=`, found `5`\n --> {}:1:7\n", path)));
+}
+
+#[test]
+fn test_tokens() {
+    let output = run_cli(&["tokens", "-"], "let x");
+    assert_eq!(exit_code::SUCCESS, output.code);
+    assert_eq!("1:1 Let\n1:5 Identifier { literal: \"x\" }\n1:6 Eof\n", output.stdout);
+}
+
+#[test]
+fn test_tokens_fails_on_lexer_error() {
+    let output = run_cli(&["tokens", "-"], "\"abc");
+    assert_eq!(exit_code::FAILURE, output.code);
+    assert_eq!("\
+error[E0002]: unterminated string literal
+ --> <stdin>:1:1
+  |
+1 | \"abc
+  | ^^^^
+  = help: add a closing `\"` to end the string
+", output.stderr);
+}
+
+#[test]
+fn test_ast() {
+    let output = run_cli(&["ast", "-"], "x");
+    assert_eq!(exit_code::SUCCESS, output.code);
+    assert!(output.stdout.starts_with("Expression(\n    Identifier {"), "{}", output.stdout);
+}
+
+#[test]
+fn test_ast_with_errors_prints_recovered_tree() {
+    let output = run_cli(&["ast", "-"], "let x = ; y");
+    assert_eq!(exit_code::FAILURE, output.code);
+    assert!(output.stdout.contains("value: Error {"), "{}", output.stdout);
+    assert!(output.stdout.contains("literal: \"y\""), "{}", output.stdout);
+    assert_eq!("\
+error[E0102]: no prefix parsing function defined for `;`
+ --> <stdin>:1:9
+  |
+1 | let x = ; y
+  |         ^
+  = help: `;` cannot start an expression
+", output.stderr);
+}
+
+#[test]
+fn test_serialized_formats() {
+    let output = run_cli(&["tokens", "--format", "sexpr", "-"], "let x = 5;");
+    assert_eq!(exit_code::SUCCESS, output.code);
+    assert_eq!("(tokens (Let) (Identifier \"x\") (Assign) (Int \"5\") (Semicolon) (Eof))\n", output.stdout);
+
+    let output = run_cli(&["ast", "--format", "json", "-"], "x");
+    assert_eq!(exit_code::SUCCESS, output.code);
+    assert_eq!(
+        "{\"version\":1,\"kind\":\"Program\",\"statements\":[{\"kind\":\"Expression\",\"expression\":\
+        {\"kind\":\"Identifier\",\"name\":\"x\",\"span\":{\"start\":0,\"end\":1,\"line\":1,\"column\":1}},\
+        \"span\":{\"start\":0,\"end\":1,\"line\":1,\"column\":1}}]}\n",
+        output.stdout,
+    );
+
+    let output = run_cli(&["ast", "--format", "sexpr", "-"], "let x = ; y");
+    assert_eq!(exit_code::FAILURE, output.code);
+    assert_eq!("(program (let x (error)) y)\n", output.stdout);
+    assert!(output.stderr.starts_with("error[E0102]"), "{}", output.stderr);
+
+    let output = run_cli(&["ast", "--format", "yaml", "-"], "x");
+    assert_eq!(exit_code::USAGE, output.code);
+    assert!(output.stderr.starts_with("unknown format: yaml\n"));
+
+    let output = run_cli(&["run", "--format", "json", "-"], "x");
+    assert_eq!(exit_code::USAGE, output.code);
+}
+
+#[test]
+fn test_disasm() {
+    let output = run_cli(&["disasm", "-"], "let x = 5;\n-x");
+    assert_eq!(exit_code::SUCCESS, output.code);
+    assert_eq!("\
+constants:
+    0  5
+
+globals:
+    0  x
+
+main:
+           1 | let x = 5;
+  0000     1  Constant 0               ; 5
+  0003     1  SetGlobal 0              ; x
+           2 | -x
+  0006     2  GetGlobal 0              ; x
+  0009     2  Negate
+           1 | let x = 5;
+  0010     1  Return
+", output.stdout);
+
+    let output = run_cli(&["disasm", "-"], "2 * 3 / 0");
+    assert_eq!(exit_code::SUCCESS, output.code);
+    assert!(output.stdout.contains("Constant 0               ; 6\n"), "{}", output.stdout);
+    assert_eq!("\
+warning[W0001]: division by zero
+ --> <stdin>:1:1
+  |
+1 | 2 * 3 / 0
+  | ^^^^^^^^^
+  = help: this fails at runtime if it is reached
+", output.stderr);
+
+    for source_code in ["", "// nothing yet\n"] {
+        let output = run_cli(&["disasm", "-"], source_code);
+        assert_eq!(exit_code::SUCCESS, output.code, "{}", output.stderr);
+        assert!(output.stdout.ends_with("main:\n  0000     -  Null\n  0001     -  Return\n"), "{}", output.stdout);
+    }
+
+    let output = run_cli(&["disasm", "-"], "<1");
+    assert_eq!(exit_code::FAILURE, output.code);
+    assert!(output.stderr.starts_with("error[E0301]: unknown operator: <\n"), "{}", output.stderr);
+}
+
+#[test]
+fn test_build_and_run_bytecode() {
+    let path = write_script("build", "let add = fn(a, b) { a + b };\nadd(40, 2)\n");
+    let output_path = path.replace(".mk", ".mkc");
+    let output = run_cli(&["build", &path], "");
+    assert_eq!(exit_code::SUCCESS, output.code, "{}", output.stderr);
+    assert_eq!("", output.stdout);
+
+    let output = run_cli(&["run", &output_path], "");
+    assert_eq!(exit_code::SUCCESS, output.code);
+    assert_eq!("42\n", output.stdout);
+    let output = run_cli(&["disasm", &output_path], "");
+    assert_eq!(exit_code::SUCCESS, output.code);
+    assert!(output.stdout.contains("function 0 add(a, b), 2 locals:\n"), "{}", output.stdout);
+
+    let error_path = path.replace(".mk", "_error.mkc");
+    let output = run_cli(&["build", "-", "-o", &error_path], "let x = 1;\nx + true");
+    assert_eq!(exit_code::SUCCESS, output.code);
+    let output = run_cli(&["run", &error_path], "");
+    assert_eq!(exit_code::FAILURE, output.code);
+    assert_eq!(format!("error[E0201]: type mismatch: INTEGER + BOOLEAN\n--> {}:2:1\n", error_path), output.stderr);
+
+    let mut bytes = fs::read(&output_path).unwrap();
+    bytes[5] += 1;
+    fs::write(&output_path, bytes).unwrap();
+    let output = run_cli(&["run", &output_path], "");
+    assert_eq!(exit_code::FAILURE, output.code);
+    assert!(output.stderr.contains("bytecode format version 4 is not supported, expected version 3"), "{}", output.stderr);
+
+    let output = run_cli(&["build", "-"], "1");
+    assert_eq!(exit_code::USAGE, output.code);
+    let output = run_cli(&["run", &path, "-o", &output_path], "");
+    assert_eq!(exit_code::USAGE, output.code);
+}
+
+#[test]
+fn test_check() {
+    let output = run_cli(&["check", "-"], "let x = 1 / 2;");
+    assert_eq!(exit_code::SUCCESS, output.code);
+    assert_eq!("", output.stdout);
+    assert_eq!("", output.stderr);
+
+    let output = run_cli(&["check", "-"], "let x = 1 / 0;");
+    assert_eq!(exit_code::SUCCESS, output.code);
+    assert_eq!("", output.stdout);
+    assert_eq!("\
+warning[W0001]: division by zero
+ --> <stdin>:1:9
+  |
+1 | let x = 1 / 0;
+  |         ^^^^^
+  = help: this fails at runtime if it is reached
+", output.stderr);
+
+    let output = run_cli(&["check", "-"], "let x 5;");
+    assert_eq!(exit_code::FAILURE, output.code);
+    assert_eq!("\
+error[E0101]: expected `=`, found `5`
+ --> <stdin>:1:7
+  |
+1 | let x 5;
+  |       ^
+", output.stderr);
+}
+
+#[test]
+fn test_fmt_stdin() {
+    let output = run_cli(&["fmt", "-"], "let x=1+2");
+    assert_eq!(exit_code::SUCCESS, output.code);
+    assert_eq!("let x = 1 + 2;\n", output.stdout);
+}
+
+#[test]
+fn test_fmt_rewrites_files() {
+    let path = write_script("fmt", "let x=1+2");
+    let output = run_cli(&["fmt", &path], "");
+    assert_eq!(exit_code::SUCCESS, output.code);
+    assert_eq!("", output.stdout);
+    assert_eq!("let x = 1 + 2;\n", fs::read_to_string(&path).unwrap());
+}
+
+#[test]
+fn test_fmt_check() {
+    let formatted = write_script("fmt_check_formatted", "let x = 1 + 2;\n");
+    let unformatted = write_script("fmt_check_unformatted", "let x=1+2");
+
+    let output = run_cli(&["fmt", "--check", &formatted], "");
+    assert_eq!(exit_code::SUCCESS, output.code);
+    assert_eq!("", output.stderr);
+
+    let output = run_cli(&["fmt", "--check", &formatted, &unformatted], "");
+    assert_eq!(exit_code::FAILURE, output.code);
+    assert_eq!(format!("{}: not formatted\n", unformatted), output.stderr);
+    assert_eq!("let x=1+2", fs::read_to_string(&unformatted).unwrap());
+
+    let output = run_cli(&["fmt", "--check", "-"], "let x 5;");
+    assert_eq!(exit_code::FAILURE, output.code);
+    assert!(output.stderr.starts_with("error[E0101]: expected `=`, found `5`\n --> <stdin>:1:7\n"));
+}
+
+#[test]
+fn test_usage_errors() {
+    let output = run_cli(&["run"], "");
+    assert_eq!(exit_code::USAGE, output.code);
+    assert!(output.stderr.starts_with("usage: tarzan"));
+
+    let output = run_cli(&["run", "--check", "-"], "");
+    assert_eq!(exit_code::USAGE, output.code);
+
+    let output = run_cli(&["fmt", "--verbose", "-"], "");
+    assert_eq!(exit_code::USAGE, output.code);
+    assert!(output.stderr.starts_with("unknown option: --verbose\n"));
+
+    let output = run_cli(&["frobnicate", "-"], "");
+    assert_eq!(exit_code::USAGE, output.code);
+    assert!(output.stderr.starts_with("unknown command: frobnicate\n"));
+
+    let output = run_cli(&["run", "/nonexistent/script.mk"], "");
+    assert_eq!(exit_code::USAGE, output.code);
+    assert!(output.stderr.starts_with("could not read /nonexistent/script.mk: "));
+}
+
+#[test]
+fn test_no_arguments_starts_repl() {
+    let output = run_cli(&[], "1 + 2\n");
+    assert_eq!(exit_code::SUCCESS, output.code);
+    assert_eq!(">> 3\n>> \n", output.stdout);
+}
+
+#[test]
+fn test_binary_exit_code() {
+    let path = write_script("binary", "1 + true");
+    let output = Command::new(env!("CARGO_BIN_EXE_tarzan")).args(["run", &path]).output().unwrap();
+    assert_eq!(Some(exit_code::FAILURE), output.status.code());
+    assert!(String::from_utf8(output.stderr).unwrap().starts_with("error[E0201]: type mismatch: INTEGER + BOOLEAN\n"));
+}
+
+#[test]
+fn test_binary_deep_recursion() {
+    let path = write_script("deep", "let f = fn(n) { if (n == 0) { 0 } else { 1 + f(n - 1) } }; f(9999)");
+    let output = Command::new(env!("CARGO_BIN_EXE_tarzan")).args(["run", &path]).output().unwrap();
+    assert_eq!(Some(exit_code::SUCCESS), output.status.code());
+    assert_eq!("9999\n", String::from_utf8(output.stdout).unwrap());
+
+    let path = write_script("overflow", "let f = fn(x) { f(x) }; f(1)");
+    let output = Command::new(env!("CARGO_BIN_EXE_tarzan")).args(["run", &path]).output().unwrap();
+    assert_eq!(Some(exit_code::FAILURE), output.status.code());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.starts_with("error[E0201]: stack overflow: more than 10000 nested calls\n"), "{}", stderr);
+}
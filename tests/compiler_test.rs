@@ -0,0 +1,321 @@
+use tarzan::{compiler, lexer, parser};
+use tarzan::ast::Program;
+use tarzan::compiler::code::{self, Opcode};
+use tarzan::compiler::disassembler;
+use tarzan::compiler::error::CompileError;
+use tarzan::compiler::symbol_table::{self, Symbol, SymbolScope};
+use tarzan::compiler::{Bytecode, Constant};
+
+fn parse(source_code: &str) -> Program {
+    let mut parser = parser::new(lexer::new(source_code.into()));
+    return parser.parse().unwrap_or_else(|errors| {
+        let messages: Vec<String> = errors.iter().map(|error| error.to_string()).collect();
+        panic!("parser errors for {:?}: {:?}", source_code, messages)
+    });
+}
+
+fn compile(source_code: &str) -> Bytecode {
+    compiler::compile(&parse(source_code))
+        .unwrap_or_else(|error| panic!("compiler error for {:?}: {}", source_code, error))
+}
+
+type Instruction<'a> = (Opcode, &'a [usize]);
+
+fn instructions(instructions: &[Instruction]) -> Vec<u8> {
+    instructions.iter().flat_map(|(opcode, operands)| code::make(*opcode, operands)).collect()
+}
+
+#[test]
+fn test_make_and_read_operands() {
+    let instruction = code::make(Opcode::Closure, &[65534]);
+    assert_eq!(vec![Opcode::Closure as u8, 255, 254], instruction);
+    assert_eq!(Some((vec![65534], 2)), code::read_operands(Opcode::Closure, &instruction[1..]));
+    assert_eq!(None, code::read_operands(Opcode::Constant, &[1]));
+
+    for byte in 0..=u8::MAX {
+        if let Some(opcode) = Opcode::from_byte(byte) {
+            assert_eq!(byte, opcode as u8, "{}", opcode.name());
+        }
+    }
+}
+
+#[test]
+fn test_arithmetic_and_comparisons() {
+    let tests: Vec<(&str, Vec<Constant>, Vec<Instruction>)> = vec![
+        ("1 + 2", vec![Constant::Integer(1), Constant::Integer(2)], vec![
+            (Opcode::Constant, &[0]), (Opcode::Constant, &[1]), (Opcode::Add, &[]), (Opcode::Return, &[]),
+        ]),
+        ("1; 2", vec![Constant::Integer(1), Constant::Integer(2)], vec![
+            (Opcode::Constant, &[0]), (Opcode::Pop, &[]), (Opcode::Constant, &[1]), (Opcode::Return, &[]),
+        ]),
+        ("2 * 2 / 2 - 2", vec![Constant::Integer(2)], vec![
+            (Opcode::Constant, &[0]), (Opcode::Constant, &[0]), (Opcode::Multiply, &[]),
+            (Opcode::Constant, &[0]), (Opcode::Divide, &[]),
+            (Opcode::Constant, &[0]), (Opcode::Subtract, &[]), (Opcode::Return, &[]),
+        ]),
+        ("1 < 2 == true != false", vec![Constant::Integer(1), Constant::Integer(2)], vec![
+            (Opcode::Constant, &[0]), (Opcode::Constant, &[1]), (Opcode::LessThan, &[]),
+            (Opcode::True, &[]), (Opcode::Equal, &[]), (Opcode::False, &[]), (Opcode::NotEqual, &[]),
+            (Opcode::Return, &[]),
+        ]),
+        ("-1 > +2", vec![Constant::Integer(1), Constant::Integer(2)], vec![
+            (Opcode::Constant, &[0]), (Opcode::Negate, &[]), (Opcode::Constant, &[1]), (Opcode::Positive, &[]),
+            (Opcode::GreaterThan, &[]), (Opcode::Return, &[]),
+        ]),
+        ("!\"a\"", vec![Constant::Str("a".into())], vec![
+            (Opcode::Constant, &[0]), (Opcode::Not, &[]), (Opcode::Return, &[]),
+        ]),
+        ("", vec![], vec![(Opcode::Null, &[]), (Opcode::Return, &[])]),
+    ];
+
+    for (source_code, constants, expected) in tests {
+        let bytecode = compile(source_code);
+        assert_eq!(constants, bytecode.constants, "constants for {:?}", source_code);
+        assert_eq!(instructions(&expected), bytecode.main.instructions, "instructions for {:?}", source_code);
+    }
+}
+
+#[test]
+fn test_conditionals() {
+    let bytecode = compile("if (true) { 10 }; 3333;");
+    assert_eq!(instructions(&[
+        (Opcode::True, &[]),            // 0000
+        (Opcode::JumpNotTruthy, &[10]), // 0001
+        (Opcode::Constant, &[0]),       // 0004
+        (Opcode::Jump, &[11]),          // 0007
+        (Opcode::Null, &[]),            // 0010
+        (Opcode::Pop, &[]),             // 0011
+        (Opcode::Constant, &[1]),       // 0012
+        (Opcode::Return, &[]),          // 0015
+    ]), bytecode.main.instructions);
+
+    let bytecode = compile("if (x) { let y = 1; } else { 2 }");
+    assert_eq!(instructions(&[
+        (Opcode::GetGlobal, &[1]),      // 0000
+        (Opcode::JumpNotTruthy, &[16]), // 0003
+        (Opcode::Constant, &[0]),       // 0006
+        (Opcode::SetGlobal, &[0]),      // 0009
+        (Opcode::Null, &[]),            // 0012
+        (Opcode::Jump, &[19]),          // 0013
+        (Opcode::Constant, &[1]),       // 0016
+        (Opcode::Return, &[]),          // 0019
+    ]), bytecode.main.instructions);
+    assert_eq!(vec!["y".to_string(), "x".to_string()], bytecode.globals);
+}
+
+#[test]
+fn test_global_let_statements() {
+    let bytecode = compile("let one = 1; let two = one; let one = two + one; one");
+    assert_eq!(instructions(&[
+        (Opcode::Constant, &[0]),
+        (Opcode::SetGlobal, &[0]),
+        (Opcode::GetGlobal, &[0]),
+        (Opcode::SetGlobal, &[1]),
+        (Opcode::GetGlobal, &[1]),
+        (Opcode::GetGlobal, &[0]),
+        (Opcode::Add, &[]),
+        (Opcode::SetGlobal, &[0]),
+        (Opcode::GetGlobal, &[0]),
+        (Opcode::Return, &[]),
+    ]), bytecode.main.instructions);
+    assert_eq!(vec!["one".to_string(), "two".to_string()], bytecode.globals);
+
+    let bytecode = compile("let x = 1;");
+    assert_eq!(instructions(&[
+        (Opcode::Constant, &[0]), (Opcode::SetGlobal, &[0]), (Opcode::Null, &[]), (Opcode::Return, &[]),
+    ]), bytecode.main.instructions);
+}
+
+#[test]
+fn test_collections_and_index() {
+    let bytecode = compile("[1, 2][0]; {\"a\": 1}");
+    assert_eq!(instructions(&[
+        (Opcode::Constant, &[0]),
+        (Opcode::Constant, &[1]),
+        (Opcode::Array, &[2]),
+        (Opcode::Constant, &[2]),
+        (Opcode::Index, &[]),
+        (Opcode::Pop, &[]),
+        (Opcode::Constant, &[3]),
+        (Opcode::CheckHashKey, &[]),
+        (Opcode::Constant, &[0]),
+        (Opcode::Hash, &[2]),
+        (Opcode::Return, &[]),
+    ]), bytecode.main.instructions);
+    assert_eq!(
+        vec![Constant::Integer(1), Constant::Integer(2), Constant::Integer(0), Constant::Str("a".into())],
+        bytecode.constants,
+    );
+}
+
+#[test]
+fn test_functions_and_calls() {
+    let bytecode = compile("let add = fn(a, b) { let c = a + b; c }; add(1, 2)");
+    let add = &bytecode.functions[0];
+    assert_eq!(Some("add".to_string()), add.name);
+    assert_eq!(vec!["a".to_string(), "b".to_string()], add.parameters);
+    assert_eq!(vec!["a".to_string(), "b".to_string(), "c".to_string()], add.locals);
+    assert_eq!(instructions(&[
+        (Opcode::GetLocal, &[0]),
+        (Opcode::GetLocal, &[1]),
+        (Opcode::Add, &[]),
+        (Opcode::SetLocal, &[2]),
+        (Opcode::GetLocal, &[2]),
+        (Opcode::Return, &[]),
+    ]), add.instructions);
+    assert_eq!(instructions(&[
+        (Opcode::Closure, &[0]),
+        (Opcode::SetGlobal, &[0]),
+        (Opcode::GetGlobal, &[0]),
+        (Opcode::Constant, &[0]),
+        (Opcode::Constant, &[1]),
+        (Opcode::Call, &[2]),
+        (Opcode::Return, &[]),
+    ]), bytecode.main.instructions);
+
+    let bytecode = compile("fn() { return 1; 2 }; fn() { }");
+    assert_eq!(instructions(&[
+        (Opcode::Constant, &[0]), (Opcode::Return, &[]), (Opcode::Constant, &[1]), (Opcode::Return, &[]),
+    ]), bytecode.functions[0].instructions);
+    assert_eq!(instructions(&[(Opcode::Null, &[]), (Opcode::Return, &[])]), bytecode.functions[1].instructions);
+}
+
+#[test]
+fn test_closures() {
+    let bytecode = compile("fn(a) { fn(b) { fn(c) { a + b + c } } }");
+    assert_eq!(instructions(&[
+        (Opcode::GetFree, &[0]),
+        (Opcode::GetFree, &[1]),
+        (Opcode::Add, &[]),
+        (Opcode::GetLocal, &[0]),
+        (Opcode::Add, &[]),
+        (Opcode::Return, &[]),
+    ]), bytecode.functions[0].instructions);
+    assert_eq!(vec![symbol("a", SymbolScope::Free, 0), symbol("b", SymbolScope::Local, 0)], bytecode.functions[0].free);
+    assert_eq!(instructions(&[(Opcode::Closure, &[0]), (Opcode::Return, &[])]), bytecode.functions[1].instructions);
+    assert_eq!(vec![symbol("a", SymbolScope::Local, 0)], bytecode.functions[1].free);
+    assert_eq!(instructions(&[(Opcode::Closure, &[1]), (Opcode::Return, &[])]), bytecode.functions[2].instructions);
+    assert!(bytecode.functions[2].free.is_empty());
+    assert_eq!(instructions(&[(Opcode::Closure, &[2]), (Opcode::Return, &[])]), bytecode.main.instructions);
+
+    let bytecode = compile("fn() { let f = fn() { x }; let x = 1; f() }");
+    assert_eq!(vec!["f".to_string(), "x".to_string()], bytecode.functions[1].locals);
+    assert_eq!(vec![symbol("x", SymbolScope::Local, 1)], bytecode.functions[0].free);
+}
+
+fn symbol(name: &str, scope: SymbolScope, index: usize) -> Symbol {
+    Symbol { name: name.to_string(), scope, index }
+}
+
+#[test]
+fn test_recursive_functions() {
+    let bytecode = compile("let wrapper = fn() { let countdown = fn(x) { countdown(x - 1) }; countdown(1) };");
+    assert_eq!(vec![symbol("countdown", SymbolScope::Local, 0)], bytecode.functions[0].free);
+    assert_eq!(instructions(&[
+        (Opcode::GetFree, &[0]),
+        (Opcode::GetLocal, &[0]),
+        (Opcode::Constant, &[0]),
+        (Opcode::Subtract, &[]),
+        (Opcode::Call, &[1]),
+        (Opcode::Return, &[]),
+    ]), bytecode.functions[0].instructions);
+    assert_eq!(instructions(&[
+        (Opcode::Closure, &[0]),
+        (Opcode::SetLocal, &[0]),
+        (Opcode::GetLocal, &[0]),
+        (Opcode::Constant, &[0]),
+        (Opcode::Call, &[1]),
+        (Opcode::Return, &[]),
+    ]), bytecode.functions[1].instructions);
+}
+
+#[test]
+fn test_instruction_spans() {
+    let bytecode = compile("let x = 1;\nx / 0");
+    let divide = instructions(&[
+        (Opcode::Constant, &[0]), (Opcode::SetGlobal, &[0]), (Opcode::GetGlobal, &[0]), (Opcode::Constant, &[1]),
+    ]).len();
+    assert_eq!(Opcode::Divide as u8, bytecode.main.instructions[divide]);
+
+    let span = bytecode.main.span_at(divide).unwrap();
+    assert_eq!((2, 1, 11, 16), (span.line, span.column, span.start, span.end));
+    let span = bytecode.main.span_at(divide - 3).unwrap();
+    assert_eq!((2, 5, 15, 16), (span.line, span.column, span.start, span.end));
+}
+
+#[test]
+fn test_symbol_table_scopes() {
+    let mut global = symbol_table::new();
+    assert_eq!(Symbol { name: "a".into(), scope: SymbolScope::Global, index: 0 }, global.define("a"));
+
+    let mut local = symbol_table::new_enclosed(global);
+    assert_eq!(Symbol { name: "b".into(), scope: SymbolScope::Local, index: 0 }, local.define("b"));
+
+    let mut nested = symbol_table::new_enclosed(local);
+    assert_eq!(Symbol { name: "c".into(), scope: SymbolScope::Local, index: 0 }, nested.define("c"));
+    assert_eq!(SymbolScope::Global, nested.resolve("a").scope);
+    assert_eq!(Symbol { name: "b".into(), scope: SymbolScope::Free, index: 0 }, nested.resolve("b"));
+    assert_eq!(vec![Symbol { name: "b".into(), scope: SymbolScope::Local, index: 0 }], nested.free_symbols);
+
+    assert_eq!(Symbol { name: "later".into(), scope: SymbolScope::Global, index: 1 }, nested.resolve("later"));
+}
+
+#[test]
+fn test_compile_errors() {
+    let mut parser = parser::new(lexer::new("let x = ;".into()));
+    let (program, _) = parser.parse_with_recovery();
+    let error = compiler::compile(&program).unwrap_err();
+    assert!(matches!(error, CompileError::SyntaxError { .. }), "{:?}", error);
+
+    let error = compiler::compile(&parse("<1")).unwrap_err();
+    assert_eq!("unknown operator: < at 1:1", error.to_string());
+}
+
+#[test]
+fn test_disassemble() {
+    let source_code = "let f = fn(x) {\n  x + 1\n};\nf(len(\"ab\"))";
+    let bytecode = compile(source_code);
+    assert_eq!("\
+constants:
+    0  1
+    1  \"ab\"
+
+globals:
+    0  f
+
+main:
+  0000     1  Closure 0                ; function 0 f
+  0003     1  SetGlobal 0              ; f
+  0006     4  GetGlobal 0              ; f
+  0009     4  GetBuiltin 0             ; len
+  0011     4  Constant 1               ; \"ab\"
+  0014     4  Call 1
+  0016     4  Call 1
+  0018     1  Return
+
+function 0 f(x), 1 locals:
+  0000     2  GetLocal 0
+  0002     2  Constant 0               ; 1
+  0005     2  Add
+  0006     1  Return
+", disassembler::disassemble(&bytecode, None));
+
+    let listing = disassembler::disassemble(&bytecode, Some(source_code));
+    assert!(listing.contains("\
+function 0 f(x), 1 locals:
+           2 |   x + 1
+  0000     2  GetLocal 0
+"), "{}", listing);
+
+    let mut broken = bytecode.clone();
+    broken.main = std::rc::Rc::new(tarzan::compiler::CompiledFunction {
+        instructions: vec![255, Opcode::Constant as u8, 0],
+        spans: Vec::new(),
+        ..(*bytecode.main).clone()
+    });
+    assert!(disassembler::disassemble(&broken, None).contains("\
+main:
+  0000     -  <invalid opcode 0xff>
+  0001     -  <truncated Constant>
+"));
+}
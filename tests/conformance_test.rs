@@ -0,0 +1,190 @@
+use tarzan::{compiler, evaluator, lexer, optimizer, parser, vm};
+use tarzan::ast::Program;
+use tarzan::evaluator::environment;
+use tarzan::evaluator::object::Object;
+use tarzan::token::Span;
+
+/// Programs that must give the same value, or the same error at the same place, whether they are
+/// run by the evaluator or compiled and run by the VM.
+const PROGRAMS: &[&str] = &[
+    // Literals and operators.
+    "5",
+    "-5 + +10 * 2 - 30 / 3",
+    "(5 + 10 * 2 + 15 / 3) * 2 + -10",
+    "1 < 2 == true != (3 > 4)",
+    "!true == !!false",
+    "!5; !!\"\"; !0",
+    "\"Hello\" + \" \" + \"World!\"",
+    "\"a\" == \"a\"; \"a\" != \"b\"",
+    "9223372036854775807",
+    // Conditionals and blocks.
+    "if (1 < 2) { 10 } else { 20 }",
+    "if (1 > 2) { 10 }",
+    "if (false) { 10 } else { }",
+    "if (0) { 1 } else { 2 }",
+    "if (true) { let x = 1; }",
+    "if (true) { if (true) { return 10; } return 1; }",
+    "let x = if (true) { 1; 2 }; x",
+    // Bindings.
+    "let a = 5; let b = a * 2; let c = a + b; c",
+    "let a = 1; let a = a + 1; a",
+    "let a = 1;",
+    "1; let a = 2;",
+    "if (true) { let x = 7; }; x",
+    "return 1; 2",
+    "",
+    // Functions.
+    "let add = fn(a, b) { a + b }; add(1, add(2, 3))",
+    "let f = fn() { 1; 2; }; f()",
+    "let f = fn() { }; f()",
+    "let f = fn() { let x = 1; }; f()",
+    "let f = fn(x) { if (x > 1) { return x; } 0 }; f(5) + f(1)",
+    "fn(x) { x * 2 }(21)",
+    "let f = fn(a, b) { a + b }; f",
+    "let g = fn() { h() }; let h = fn() { 42 }; g()",
+    "let global = 10; let f = fn() { let local = 5; global + local }; f() + f()",
+    "let factorial = fn(n) { if (n < 2) { 1 } else { n * factorial(n - 1) } }; factorial(20)",
+    "let fib = fn(n) { if (n < 2) { n } else { fib(n - 1) + fib(n - 2) } }; fib(15)",
+    "let outer = fn() { let inner = fn(n) { if (n == 0) { 0 } else { inner(n - 1) } }; inner(3) }; outer()",
+    "let apply = fn(f, x) { f(x) }; apply(fn(x) { x + 1 }, 1)",
+    // Closures.
+    "let adder = fn(x) { fn(y) { x + y } }; adder(2)(3)",
+    "let adder = fn(x) { fn(y) { x + y } }; let add_two = adder(2); let add_ten = adder(10); add_two(1) + add_ten(1)",
+    "let f = fn(a) { fn(b) { fn(c) { a + b + c } } }; f(1)(2)(3)",
+    "let f = fn(a) { let g = fn() { a * 2 }; let h = fn() { g() + 1 }; h }; f(20)()",
+    "let f = fn() { let g = fn(n) { if (n == 0) { 0 } else { g(n - 1) } }; g }; f()(10)",
+    "let compose = fn(f, g) { fn(x) { g(f(x)) } }; compose(fn(x) { x + 1 }, fn(x) { x * 2 })(5)",
+    "let f = fn(x) { fn() { x / 0 } }; f(1)()",
+    // Scoping. A name refers to the innermost function that binds it, with `let` anywhere in its
+    // body or as a parameter, where the function is defined rather than where it is called.
+    "let x = 1; let f = fn() { x }; let g = fn(x) { f() }; g(100)",
+    "let f = fn() { z }; let g = fn() { let z = 1; f() }; g()",
+    "let make = fn() { let n = 7; fn() { n } }; let n = 1; make()()",
+    "let x = 1; let f = fn(x) { fn() { x } }; f(2)() + x",
+    "let f = fn() { y }; let y = 2; f()",
+    "let f = fn() { let a = fn() { b() }; let b = fn() { 1 }; a() }; f()",
+    "let f = fn() { let x = 1; let g = fn() { x }; let x = 2; g() }; f()",
+    "let f = fn() { let g = fn() { x }; let r = g(); let x = 1; r }; f()",
+    "let f = fn() { let len = len([1, 2]); len }; f()",
+    "let x = 1; let f = fn() { let y = x; let x = 2; y }; f()",
+    "let counter = fn() { let n = 0; let get = fn() { n }; let n = n + 1; get }; counter()()",
+    // Collections.
+    "[1, 2 * 2, 3 + 3]",
+    "[1, 2, 3][1 + 1]; [1, 2, 3][3]; [1][-1]",
+    "let a = [[1, 2], [3]]; a[0][1] + a[1][0]",
+    "{\"one\": 1, \"two\": 1 + 1, 3: true, false: \"no\"}",
+    "let h = {\"a\": 1}; h[\"a\"]; h[\"b\"]",
+    "{1: 1, 1: 2}[1]",
+    // Builtins.
+    "len(\"\"); len(\"four\"); len([1, 2, 3])",
+    "first([1, 2]); last([1, 2]); rest([1, 2, 3]); first([]); rest([])",
+    "push([1], 2)",
+    "let len = fn(x) { 0 }; len([1])",
+    "let map = fn(xs, f) { if (len(xs) == 0) { [] } else { push(map(rest(xs), f), f(first(xs))) } }; map([1, 2, 3], fn(x) { x * x })",
+    "len",
+    "puts(1, \"a\"); let f = fn(x) { puts(x); x }; f([1]); puts()",
+    // Runtime errors.
+    "5 + true",
+    "5 + true; 5",
+    "-true",
+    "+\"a\"",
+    "true + false",
+    "if (10 > 1) { true + false; }",
+    "\"a\" - \"b\"",
+    "foobar",
+    "let a = b; a",
+    "10 / 0",
+    "let f = fn(x) { x / 0 }; f(1)",
+    "9223372036854775807 + 1",
+    "-(-9223372036854775807 - 1)",
+    "let f = fn(x) { x }; f(1, 2)",
+    "let f = 5; f(1)",
+    "1[0]",
+    "{\"a\": 1}[[1]]",
+    "{[1]: 2}",
+    "{[1]: 1 / 0}",
+    "{1: 1, fn() { 1 }: missing}",
+    "{1: 1 / 0, [1]: 2}",
+    "len(1)",
+    "len(\"a\", \"b\")",
+    "first(1)",
+    "push(1, 1)",
+    "let f = fn() { let a = 1; a }; f(); a",
+    "let f = fn() { missing }; let g = fn() { f() }; g()",
+    // Constant expressions, which the optimizer folds.
+    "if (1 + 1 == 2) { 10 } else { 1 / 0 }",
+    "if (!true) { 1 }",
+    "let x = 3; x * (2 + 2) - -x",
+    "1 / (2 - 2)",
+    "2 * (9223372036854775807 - 1)",
+    "true + (1 < 2)",
+    "let x = 1; let f = fn() { if (false) { let x = 2; } x }; f()",
+    "let y = 1; let f = fn() { if (true) { 0 } else { let y = 2; } y }; f()",
+    "if (false) { let z = 1; } z",
+];
+
+/// Programs that nest calls as deeply as the VM allows, or deeper. They run on the test's own
+/// thread, which has a default-sized stack, although the evaluator recurses natively for every call.
+const DEEP_PROGRAMS: &[&str] = &[
+    "let f = fn(x) { f(x) }; f(1)",
+    "let f = fn(n) { if (n == 0) { 0 } else { 1 + f(n - 1) } }; f(9999)",
+    "let f = fn(n) { if (n == 0) { 0 } else { 1 + f(n - 1) } }; f(10000)",
+    "let f = fn(n) { if (n == 0) { 0 } else { let g = fn() { f(n - 1) }; g() } }; f(4999)",
+    "let f = fn(n) { if (n == 0) { 0 } else { let g = fn() { f(n - 1) }; g() } }; f(5000)",
+];
+
+fn parse(source_code: &str) -> Program {
+    let mut parser = parser::new(lexer::new(source_code.into()));
+    return parser.parse().unwrap_or_else(|errors| panic!("parser errors for {:?}: {:?}", source_code, errors));
+}
+
+/// What a program wrote with `puts`, and its printed result or its error message and span.
+type Outcome = (String, Result<String, (String, Option<Span>)>);
+
+fn evaluate(program: &Program) -> Outcome {
+    let mut output = Vec::new();
+    let result = match evaluator::eval_with_output(program, &mut environment::new(), &mut output) {
+        Object::Error(error) => Err((error.message, error.span)),
+        result => Ok(result.to_string()),
+    };
+    return (String::from_utf8(output).unwrap(), result);
+}
+
+fn compile_and_run(program: &Program) -> Outcome {
+    let bytecode = match compiler::compile(program) {
+        Ok(bytecode) => bytecode,
+        Err(error) => return (String::new(), Err((error.message(), Some(error.span())))),
+    };
+    let mut output = Vec::new();
+    let result = match vm::run_with_output(&bytecode, &mut output) {
+        Ok(result) => Ok(result.to_string()),
+        Err(error) => Err((error.message, error.span)),
+    };
+    return (String::from_utf8(output).unwrap(), result);
+}
+
+#[test]
+fn test_evaluator_and_vm_agree() {
+    for source_code in PROGRAMS {
+        let program = parse(source_code);
+        assert_eq!(evaluate(&program), compile_and_run(&program), "source: {}", source_code);
+    }
+}
+
+#[test]
+fn test_deep_recursion_agrees() {
+    for source_code in DEEP_PROGRAMS {
+        let program = parse(source_code);
+        assert_eq!(evaluate(&program), compile_and_run(&program), "source: {}", source_code);
+    }
+}
+
+#[test]
+fn test_optimized_programs_agree() {
+    for source_code in PROGRAMS {
+        let program = parse(source_code);
+        let (optimized, _) = optimizer::optimize(program.clone());
+        assert_eq!(evaluate(&program), evaluate(&optimized), "source: {}", source_code);
+        assert_eq!(evaluate(&program), compile_and_run(&optimized), "source: {}", source_code);
+    }
+}
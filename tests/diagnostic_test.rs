@@ -0,0 +1,93 @@
+use tarzan::{evaluator, lexer, parser};
+use tarzan::diagnostic::{code, Diagnostic, Style};
+use tarzan::evaluator::environment;
+use tarzan::evaluator::object::Object;
+use tarzan::lexer::error::LexError;
+
+fn render_parse_errors(source_code: &str, style: Style) -> String {
+    let mut parser = parser::new(lexer::new(source_code.into()));
+    let errors = parser.parse().expect_err("expected parser errors");
+    return errors.iter().map(|error| Diagnostic::from(error).render("test.mk", source_code, style)).collect();
+}
+
+fn render_runtime_error(source_code: &str) -> String {
+    let mut parser = parser::new(lexer::new(source_code.into()));
+    let program = parser.parse().unwrap_or_else(|errors| panic!("found parser errors: {:?}", errors));
+    match evaluator::eval(&program, &mut environment::new()) {
+        Object::Error(error) => Diagnostic::from(&error).render("test.mk", source_code, Style::Plain),
+        result => panic!("expected a runtime error, got: {}", result),
+    }
+}
+
+#[test]
+fn test_render_unclosed_delimiter_with_help() {
+    assert_eq!("\
+error[E0104]: unclosed delimiter `{`
+ --> test.mk:2:12
+  |
+2 | if (x > 1) { x
+  |            ^
+  = help: expected `}` to close `{` opened here
+", render_parse_errors("let x = 5;\nif (x > 1) { x", Style::Plain));
+}
+
+#[test]
+fn test_render_underlines_whole_span() {
+    assert_eq!("\
+error[E0103]: integer literal `99999999999999999999` does not fit in 64 bits
+ --> test.mk:1:9
+  |
+1 | let x = 99999999999999999999;
+  |         ^^^^^^^^^^^^^^^^^^^^
+  = help: integers must be between -9223372036854775808 and 9223372036854775807
+", render_parse_errors("let x = 99999999999999999999;", Style::Plain));
+}
+
+#[test]
+fn test_render_keeps_tabs_aligned_and_widens_gutter() {
+    let source_code = format!("{}\tlet x 5;", "\n".repeat(9));
+    assert_eq!("\
+error[E0101]: expected `=`, found `5`
+  --> test.mk:10:8
+   |
+10 | \tlet x 5;
+   | \t      ^
+", render_parse_errors(&source_code, Style::Plain));
+}
+
+#[test]
+fn test_render_error_at_end_of_input() {
+    assert_eq!("\
+error[E0102]: no prefix parsing function defined for `end of input`
+ --> test.mk:1:8
+  |
+1 | let x =
+  |        ^
+  = help: expected an expression before the end of input
+", render_parse_errors("let x =", Style::Plain));
+}
+
+#[test]
+fn test_render_ansi() {
+    let rendered = render_parse_errors("let x 5;", Style::Ansi);
+    assert!(rendered.starts_with("\x1b[1;31merror[E0101]\x1b[0m\x1b[1m: expected `=`, found `5`\x1b[0m\n"), "{:?}", rendered);
+    assert!(rendered.contains("\x1b[1;31m^\x1b[0m"), "{:?}", rendered);
+}
+
+#[test]
+fn test_render_without_span() {
+    let diagnostic = Diagnostic::from(&LexError::EndOfInput);
+    assert_eq!(code::END_OF_INPUT, diagnostic.code);
+    assert_eq!("error[E0001]: source code already tokenized\n--> test.mk\n", diagnostic.render("test.mk", "", Style::Plain));
+}
+
+#[test]
+fn test_render_runtime_error_at_raising_expression() {
+    assert_eq!("\
+error[E0201]: type mismatch: INTEGER * BOOLEAN
+ --> test.mk:2:21
+  |
+2 | let f = fn(x) { 1 + x * true };
+  |                     ^^^^^^^^
+", render_runtime_error("let y = true;\nlet f = fn(x) { 1 + x * true };\nf(2)"));
+}
@@ -0,0 +1,296 @@
+use std::collections::BTreeMap;
+use tarzan::{evaluator, lexer, parser};
+use tarzan::evaluator::environment;
+use tarzan::evaluator::object::{HashKey, Object};
+
+fn eval(source_code: &str) -> Object {
+    let lexer = lexer::new(source_code.into());
+    let mut parser = parser::new(lexer);
+    let program = parser.parse().unwrap_or_else(|errors| panic!("found parser errors: {:?}", errors));
+
+    let mut environment = environment::new();
+    evaluator::eval(&program, &mut environment)
+}
+
+struct EvalTestCase {
+    source_code: &'static str,
+    expected: Object,
+}
+
+fn assert_eval_test_cases(test_cases: Vec<EvalTestCase>) {
+    for test_case in test_cases {
+        assert_eq!(test_case.expected, eval(test_case.source_code), "source code: {}", test_case.source_code);
+    }
+}
+
+#[test]
+fn test_eval_integer_expressions() {
+    assert_eval_test_cases(vec![
+        EvalTestCase { source_code: "5;", expected: Object::Integer(5) },
+        EvalTestCase { source_code: "10;", expected: Object::Integer(10) },
+        EvalTestCase { source_code: "-5;", expected: Object::Integer(-5) },
+        EvalTestCase { source_code: "-10;", expected: Object::Integer(-10) },
+        EvalTestCase { source_code: "5 + 5 + 5 + 5 - 10;", expected: Object::Integer(10) },
+        EvalTestCase { source_code: "2 * 2 * 2 * 2 * 2;", expected: Object::Integer(32) },
+        EvalTestCase { source_code: "5 * 2 + 10;", expected: Object::Integer(20) },
+        EvalTestCase { source_code: "5 + 2 * 10;", expected: Object::Integer(25) },
+        EvalTestCase { source_code: "50 / 2 * 2 + 10;", expected: Object::Integer(60) },
+        EvalTestCase { source_code: "2 * (5 + 10);", expected: Object::Integer(30) },
+        EvalTestCase { source_code: "(5 + 10 * 2 + 15 / 3) * 2 + -10;", expected: Object::Integer(50) },
+    ]);
+}
+
+#[test]
+fn test_eval_boolean_expressions() {
+    assert_eval_test_cases(vec![
+        EvalTestCase { source_code: "true;", expected: Object::Boolean(true) },
+        EvalTestCase { source_code: "false;", expected: Object::Boolean(false) },
+        EvalTestCase { source_code: "1 < 2;", expected: Object::Boolean(true) },
+        EvalTestCase { source_code: "1 > 2;", expected: Object::Boolean(false) },
+        EvalTestCase { source_code: "1 == 1;", expected: Object::Boolean(true) },
+        EvalTestCase { source_code: "1 != 1;", expected: Object::Boolean(false) },
+        EvalTestCase { source_code: "true == true;", expected: Object::Boolean(true) },
+        EvalTestCase { source_code: "true != false;", expected: Object::Boolean(true) },
+        EvalTestCase { source_code: "(1 < 2) == true;", expected: Object::Boolean(true) },
+        EvalTestCase { source_code: "(1 > 2) == true;", expected: Object::Boolean(false) },
+    ]);
+}
+
+#[test]
+fn test_eval_bang_operator() {
+    assert_eval_test_cases(vec![
+        EvalTestCase { source_code: "!true;", expected: Object::Boolean(false) },
+        EvalTestCase { source_code: "!false;", expected: Object::Boolean(true) },
+        EvalTestCase { source_code: "!5;", expected: Object::Boolean(false) },
+        EvalTestCase { source_code: "!!true;", expected: Object::Boolean(true) },
+        EvalTestCase { source_code: "!!5;", expected: Object::Boolean(true) },
+    ]);
+}
+
+#[test]
+fn test_eval_string_expressions() {
+    assert_eval_test_cases(vec![
+        EvalTestCase { source_code: "\"Hello World!\";", expected: Object::Str("Hello World!".into()) },
+        EvalTestCase { source_code: "\"Hello\" + \" \" + \"World!\";", expected: Object::Str("Hello World!".into()) },
+        EvalTestCase { source_code: "\"a\\tb\";", expected: Object::Str("a\tb".into()) },
+        EvalTestCase { source_code: "let grüße = \"héllo \" + \"🐒\"; grüße;", expected: Object::Str("héllo 🐒".into()) },
+        EvalTestCase { source_code: "\"a\" == \"a\";", expected: Object::Boolean(true) },
+        EvalTestCase { source_code: "\"a\" != \"a\";", expected: Object::Boolean(false) },
+        EvalTestCase { source_code: "\"a\" - \"b\";", expected: Object::Error("unknown operator: STRING - STRING".into()) },
+        EvalTestCase { source_code: "\"a\" + 1;", expected: Object::Error("type mismatch: STRING + INTEGER".into()) },
+    ]);
+}
+
+#[test]
+fn test_eval_array_literals() {
+    let array = eval("[1, 2 * 2, 3 + 3]");
+    assert_eq!(Object::Array(vec![Object::Integer(1), Object::Integer(4), Object::Integer(6)]), array);
+    assert_eq!("[1, 4, 6]", array.to_string());
+}
+
+#[test]
+fn test_eval_array_index_expressions() {
+    assert_eval_test_cases(vec![
+        EvalTestCase { source_code: "[1, 2, 3][0];", expected: Object::Integer(1) },
+        EvalTestCase { source_code: "[1, 2, 3][2];", expected: Object::Integer(3) },
+        EvalTestCase { source_code: "let i = 0; [1][i];", expected: Object::Integer(1) },
+        EvalTestCase { source_code: "[1, 2, 3][1 + 1];", expected: Object::Integer(3) },
+        EvalTestCase { source_code: "let a = [1, 2, 3]; a[0] + a[1] + a[2];", expected: Object::Integer(6) },
+        EvalTestCase { source_code: "[1, 2, 3][3];", expected: Object::Null },
+        EvalTestCase { source_code: "[1, 2, 3][-1];", expected: Object::Null },
+        EvalTestCase { source_code: "[1][true];", expected: Object::Error("index operator not supported: ARRAY[BOOLEAN]".into()) },
+        EvalTestCase { source_code: "1[0];", expected: Object::Error("index operator not supported: INTEGER[INTEGER]".into()) },
+    ]);
+}
+
+#[test]
+fn test_eval_hash_literals() {
+    let source_code = "
+        let two = \"two\";
+        {
+            \"one\": 10 - 9,
+            two: 1 + 1,
+            \"thr\" + \"ee\": 6 / 2,
+            4: 4,
+            true: 5,
+            false: 6
+        }";
+
+    let expected = BTreeMap::from([
+        (HashKey::Str("one".into()), Object::Integer(1)),
+        (HashKey::Str("two".into()), Object::Integer(2)),
+        (HashKey::Str("three".into()), Object::Integer(3)),
+        (HashKey::Integer(4), Object::Integer(4)),
+        (HashKey::Boolean(true), Object::Integer(5)),
+        (HashKey::Boolean(false), Object::Integer(6)),
+    ]);
+
+    assert_eq!(Object::Hash(expected), eval(source_code));
+}
+
+#[test]
+fn test_eval_hash_index_expressions() {
+    assert_eval_test_cases(vec![
+        EvalTestCase { source_code: "{\"foo\": 5}[\"foo\"];", expected: Object::Integer(5) },
+        EvalTestCase { source_code: "{\"foo\": 5}[\"bar\"];", expected: Object::Null },
+        EvalTestCase { source_code: "let key = \"foo\"; {\"foo\": 5}[key];", expected: Object::Integer(5) },
+        EvalTestCase { source_code: "{}[\"foo\"];", expected: Object::Null },
+        EvalTestCase { source_code: "{5: 5}[5];", expected: Object::Integer(5) },
+        EvalTestCase { source_code: "{true: 5}[true];", expected: Object::Integer(5) },
+        EvalTestCase { source_code: "{\"name\": \"Monkey\"}[fn(x) { x }];", expected: Object::Error("unusable as hash key: FUNCTION".into()) },
+        EvalTestCase { source_code: "{[1]: 2};", expected: Object::Error("unusable as hash key: ARRAY".into()) },
+    ]);
+}
+
+#[test]
+fn test_eval_if_expressions() {
+    assert_eval_test_cases(vec![
+        EvalTestCase { source_code: "if (true) { 10 }", expected: Object::Integer(10) },
+        EvalTestCase { source_code: "if (false) { 10 }", expected: Object::Null },
+        EvalTestCase { source_code: "if (1) { 10 }", expected: Object::Integer(10) },
+        EvalTestCase { source_code: "if (1 < 2) { 10 }", expected: Object::Integer(10) },
+        EvalTestCase { source_code: "if (1 > 2) { 10 } else { 20 }", expected: Object::Integer(20) },
+        EvalTestCase { source_code: "if (1 < 2) { 10 } else { 20 }", expected: Object::Integer(10) },
+    ]);
+}
+
+#[test]
+fn test_eval_return_statements() {
+    assert_eval_test_cases(vec![
+        EvalTestCase { source_code: "return 10;", expected: Object::Integer(10) },
+        EvalTestCase { source_code: "return 10; 9;", expected: Object::Integer(10) },
+        EvalTestCase { source_code: "return 2 * 5; 9;", expected: Object::Integer(10) },
+        EvalTestCase { source_code: "9; return 2 * 5; 9;", expected: Object::Integer(10) },
+        EvalTestCase { source_code: "if (10 > 1) { if (10 > 1) { return 10; } return 1; }", expected: Object::Integer(10) },
+    ]);
+}
+
+#[test]
+fn test_eval_let_statements() {
+    assert_eval_test_cases(vec![
+        EvalTestCase { source_code: "let a = 5; a;", expected: Object::Integer(5) },
+        EvalTestCase { source_code: "let a = 5 * 5; a;", expected: Object::Integer(25) },
+        EvalTestCase { source_code: "let a = 5; let b = a; b;", expected: Object::Integer(5) },
+        EvalTestCase { source_code: "let a = 5; let b = a; let c = a + b + 5; c;", expected: Object::Integer(15) },
+        EvalTestCase { source_code: "let a = 5\nlet b = a * 2\nb", expected: Object::Integer(10) },
+    ]);
+}
+
+#[test]
+fn test_eval_function_application() {
+    assert_eval_test_cases(vec![
+        EvalTestCase { source_code: "let identity = fn(x) { x; }; identity(5);", expected: Object::Integer(5) },
+        EvalTestCase { source_code: "let identity = fn(x) { return x; }; identity(5);", expected: Object::Integer(5) },
+        EvalTestCase { source_code: "let double = fn(x) { x * 2; }; double(5);", expected: Object::Integer(10) },
+        EvalTestCase { source_code: "let add = fn(x, y) { x + y; }; add(5, 5);", expected: Object::Integer(10) },
+        EvalTestCase { source_code: "let add = fn(x, y) { x + y; }; add(5 + 5, add(5, 5));", expected: Object::Integer(20) },
+        EvalTestCase { source_code: "fn(x) { x; }(5)", expected: Object::Integer(5) },
+        EvalTestCase { source_code: "let f = fn() { let a = 1; a }; f(); a;", expected: Object::Error("identifier not found: a".into()) },
+        EvalTestCase { source_code: "let f = fn(x) { x }; f(1, 2);", expected: Object::Error("wrong number of arguments: expected 1, got 2".into()) },
+        EvalTestCase { source_code: "let f = 5; f(1);", expected: Object::Error("not a function: INTEGER".into()) },
+    ]);
+}
+
+#[test]
+fn test_eval_recursive_function() {
+    let source_code = "
+        let factorial = fn(n) { if (n < 2) { 1 } else { n * factorial(n - 1) } };
+        factorial(5);";
+    assert_eq!(Object::Integer(120), eval(source_code));
+}
+
+#[test]
+fn test_eval_closures() {
+    assert_eval_test_cases(vec![
+        EvalTestCase { source_code: "let adder = fn(x) { fn(y) { x + y } }; adder(2)(3);", expected: Object::Integer(5) },
+        EvalTestCase {
+            source_code: "let adder = fn(x) { fn(y) { x + y } }; let add_two = adder(2); let add_ten = adder(10); add_two(1) + add_ten(1);",
+            expected: Object::Integer(14),
+        },
+        EvalTestCase {
+            source_code: "let x = 1; let f = fn() { x }; let g = fn(x) { f() }; g(100);",
+            expected: Object::Integer(1),
+        },
+        EvalTestCase {
+            source_code: "let counter = fn(n) { fn() { n } }; let make = fn() { let n = 7; counter(n) }; make()();",
+            expected: Object::Integer(7),
+        },
+        EvalTestCase {
+            source_code: "let f = fn() { let g = fn(n) { if (n == 0) { 0 } else { g(n - 1) } }; g }; f()(10);",
+            expected: Object::Integer(0),
+        },
+        EvalTestCase { source_code: "let f = fn() { let y = 1; }; f(); y;", expected: Object::Error("identifier not found: y".into()) },
+        EvalTestCase {
+            source_code: "let f = fn() { z }; let g = fn() { let z = 1; f() }; g();",
+            expected: Object::Error("identifier not found: z".into()),
+        },
+        EvalTestCase {
+            source_code: "let f = fn() { let x = 1; let g = fn() { x }; let x = 2; g() }; f();",
+            expected: Object::Integer(2),
+        },
+        EvalTestCase {
+            source_code: "let x = 1; let f = fn() { let y = x; let x = 2; y }; f();",
+            expected: Object::Error("identifier not found: x".into()),
+        },
+        EvalTestCase { source_code: "let f = fn() { let len = len([1]); len }; f();", expected: Object::Integer(1) },
+    ]);
+}
+
+#[test]
+fn test_eval_builtin_functions() {
+    assert_eval_test_cases(vec![
+        EvalTestCase { source_code: "len(\"\");", expected: Object::Integer(0) },
+        EvalTestCase { source_code: "len(\"four\");", expected: Object::Integer(4) },
+        EvalTestCase { source_code: "len([1, 2, 3]);", expected: Object::Integer(3) },
+        EvalTestCase { source_code: "first([1, 2]);", expected: Object::Integer(1) },
+        EvalTestCase { source_code: "last([1, 2]);", expected: Object::Integer(2) },
+        EvalTestCase { source_code: "first([]);", expected: Object::Null },
+        EvalTestCase { source_code: "rest([1, 2, 3]);", expected: Object::Array(vec![Object::Integer(2), Object::Integer(3)]) },
+        EvalTestCase { source_code: "push([1], 2);", expected: Object::Array(vec![Object::Integer(1), Object::Integer(2)]) },
+        EvalTestCase { source_code: "let len = fn(x) { 0 }; len([1]);", expected: Object::Integer(0) },
+        EvalTestCase { source_code: "len(1);", expected: Object::Error("argument to `len` not supported, got INTEGER".into()) },
+        EvalTestCase {
+            source_code: "len(\"a\", \"b\");",
+            expected: Object::Error("wrong number of arguments to `len`: expected 1, got 2".into()),
+        },
+        EvalTestCase { source_code: "first(1);", expected: Object::Error("argument to `first` must be ARRAY, got INTEGER".into()) },
+    ]);
+}
+
+#[test]
+fn test_eval_errors() {
+    assert_eval_test_cases(vec![
+        EvalTestCase { source_code: "5 + true;", expected: Object::Error("type mismatch: INTEGER + BOOLEAN".into()) },
+        EvalTestCase { source_code: "5 + true; 5;", expected: Object::Error("type mismatch: INTEGER + BOOLEAN".into()) },
+        EvalTestCase { source_code: "-true;", expected: Object::Error("unknown operator: -BOOLEAN".into()) },
+        EvalTestCase { source_code: "true + false;", expected: Object::Error("unknown operator: BOOLEAN + BOOLEAN".into()) },
+        EvalTestCase { source_code: "if (10 > 1) { true + false; }", expected: Object::Error("unknown operator: BOOLEAN + BOOLEAN".into()) },
+        EvalTestCase { source_code: "foobar;", expected: Object::Error("identifier not found: foobar".into()) },
+        EvalTestCase { source_code: "10 / 0;", expected: Object::Error("division by zero: 10 / 0".into()) },
+        EvalTestCase { source_code: "let a = b; a;", expected: Object::Error("identifier not found: b".into()) },
+    ]);
+}
+
+#[test]
+fn test_eval_function_object() {
+    let function = eval("fn(x) { x + 2; }");
+    match function {
+        Object::Function { parameters, body, .. } => {
+            assert_eq!(1, parameters.len());
+            assert_eq!(1, body.statements.len());
+        }
+        _ => panic!("object is not a function, got: {:?}", function)
+    }
+}
+
+#[test]
+fn test_eval_function_bound_in_its_own_scope() {
+    let program = parser::new(lexer::new("let f = fn() { f };".into())).parse().unwrap();
+    let mut environment = environment::new();
+    evaluator::eval(&program, &mut environment);
+    match environment.get("f") {
+        Some(Object::Function { environment: captured, .. }) => assert_eq!(environment, captured),
+        value => panic!("f is not a function, got: {:?}", value),
+    }
+    assert_eq!(Object::Integer(3), eval("let f = fn(n) { if (n == 0) { 0 } else { 1 + f(n - 1) } }; let g = f; g(3);"));
+}
@@ -0,0 +1,148 @@
+use tarzan::formatter;
+use tarzan::formatter::FormatOptions;
+
+fn format(source_code: &str) -> String {
+    return formatter::format(source_code).unwrap_or_else(|errors| {
+        let messages: Vec<String> = errors.iter().map(|error| error.to_string()).collect();
+        panic!("parser errors for {:?}: {:?}", source_code, messages)
+    });
+}
+
+#[test]
+fn test_format_canonical_style() {
+    let source_code = "let add=fn(a,b){a+b}\nlet   result = add( 1,2*3 )\nif(result>5){return true}else{false}";
+    assert_eq!("\
+let add = fn(a, b) {
+    a + b;
+};
+let result = add(1, 2 * 3);
+if (result > 5) {
+    return true;
+} else {
+    false;
+};
+", format(source_code));
+}
+
+#[test]
+fn test_format_keeps_one_blank_line_between_statements() {
+    assert_eq!("let a = 1;\n\nlet b = 2;\nlet c = 3;\n", format("\n\nlet a = 1;\n\n\n\nlet b = 2;\nlet c = 3;\n\n"));
+}
+
+#[test]
+fn test_format_preserves_comments() {
+    let source_code = "\
+// header
+let f = fn(x) { // opens
+  /* before */ x
+  // last
+}; // after f
+
+
+/* footer */";
+    assert_eq!("\
+// header
+let f = fn(x) {
+    // opens
+    /* before */
+    x;
+    // last
+}; // after f
+
+/* footer */
+", format(source_code));
+}
+
+#[test]
+fn test_format_keeps_comments_inside_lists() {
+    assert_eq!("let x = [\n    // first\n    1,\n    2\n];\n", format("let x = [\n  // first\n  1,\n  2\n];"));
+    assert_eq!("let x = [1, 2]; // after\n", format("let x = [1,\n 2]; // after"));
+
+    let source_code = "\
+add(1, // one
+  2 /* two */, fn(x) {
+    x // body
+  }
+  // end
+);
+let h = { // opens
+  \"a\": 1,
+  \"b\": 2 };";
+    assert_eq!("\
+add(
+    1, // one
+    2, /* two */
+    fn(x) {
+        x; // body
+    }
+    // end
+);
+let h = { // opens
+    \"a\": 1,
+    \"b\": 2
+};
+", format(source_code));
+}
+
+#[test]
+fn test_format_keeps_comments_inside_expressions() {
+    assert_eq!("let x = 1 + /* inline */ 2;\n", format("let x = 1 + /* inline */ 2;"));
+    assert_eq!("let x = -/* negated */ y;\n", format("let x = - /* negated */ y;"));
+    assert_eq!("if (/* first */ x > 1) {\n    x;\n};\n", format("if (/* first */ x > 1) { x }"));
+    assert_eq!("let y = 1 + // why two\n    2;\n", format("let y = 1 +\n  // why two\n  2;"));
+}
+
+#[test]
+fn test_format_moves_comments_after_last_operand() {
+    assert_eq!("let x = 1 + 2; /* after */\n", format("let x = 1 + 2 /* after */;"));
+    assert_eq!(
+        "if (x) {\n    /* condition */\n    /* gap */\n    x;\n};\n",
+        format("if (x /* condition */) /* gap */ { x }")
+    );
+}
+
+#[test]
+fn test_format_keeps_block_holding_only_comments() {
+    assert_eq!("if (x) {\n    // nothing yet\n};\n", format("if (x) { // nothing yet\n}"));
+    assert_eq!("fn() {};\n", format("fn() {  }"));
+}
+
+#[test]
+fn test_format_wraps_long_lists() {
+    let options = FormatOptions { line_width: 20 };
+    let formatted = formatter::format_with_options("let x = add(first, second, [1, 2, 3]);", options).unwrap();
+    assert_eq!("\
+let x = add(
+    first,
+    second,
+    [1, 2, 3]
+);
+", formatted);
+
+    let formatted = formatter::format_with_options("map(xs, fn(x) { x })", options).unwrap();
+    assert_eq!("map(xs, fn(x) {\n    x;\n});\n", formatted);
+}
+
+#[test]
+fn test_format_is_idempotent() {
+    let sources = [
+        "let a=1;/* one */let b={\"k\":[1,2],true:fn(){}}; // two\n\n\nb[\"k\"][0]",
+        "let fib = fn(n) { if (n < 2) { n } else { fib(n - 1) + fib(n - 2) } }; fib(10) // done",
+        "reduce(numbers, 0, fn(accumulator, element) { accumulator + element * someLongMultiplierName });",
+        "",
+        "// only a comment",
+        "f(a, // a\n  b, [/* empty */], fn(x, // x\n y) { x })",
+        "let y = 1 +\n  // why two\n  2 * /* three */ 3;",
+    ];
+
+    for source_code in sources {
+        let formatted = format(source_code);
+        assert_eq!(formatted, format(&formatted), "source: {:?}", source_code);
+    }
+}
+
+#[test]
+fn test_format_reports_parse_errors() {
+    let errors = formatter::format("let x 5;").unwrap_err();
+    assert_eq!("expected `=`, found `5` at 1:7", errors[0].to_string());
+}
@@ -1,5 +1,7 @@
 use tarzan::lexer;
-use tarzan::token::Token;
+use tarzan::lexer::LexerOptions;
+use tarzan::lexer::error::LexError;
+use tarzan::token::{Span, Token, Trivia, TriviaKind};
 
 #[test]
 fn test_lexer_complete() {
@@ -13,7 +15,7 @@ fn test_lexer_complete() {
     };
 
     let result = add(five, ten);
-    !-/*5;
+    !-/ *5;
     5 < 10 > 5;
 
     if (5 < 10) {
@@ -104,7 +106,268 @@ fn test_lexer_complete() {
 
     let mut lexer = lexer::new(source_code);
     for expected_token in expected_tokens {
-        let token = lexer.next_token().unwrap();
+        let token = lexer.next_token().unwrap().token;
         assert_eq!(expected_token, token)
     }
-}
\ No newline at end of file
+}
+#[test]
+fn test_lexer_spans() {
+    let source_code = "let x = 10;\n  x == 5".into();
+
+    let expected_spans = [
+        (Token::Let, Span { start: 0, end: 3, line: 1, column: 1 }),
+        (Token::Identifier { literal: "x".into() }, Span { start: 4, end: 5, line: 1, column: 5 }),
+        (Token::Assign, Span { start: 6, end: 7, line: 1, column: 7 }),
+        (Token::Int { literal: "10".into() }, Span { start: 8, end: 10, line: 1, column: 9 }),
+        (Token::Semicolon, Span { start: 10, end: 11, line: 1, column: 11 }),
+        (Token::Identifier { literal: "x".into() }, Span { start: 14, end: 15, line: 2, column: 3 }),
+        (Token::Equal, Span { start: 16, end: 18, line: 2, column: 5 }),
+        (Token::Int { literal: "5".into() }, Span { start: 19, end: 20, line: 2, column: 8 }),
+        (Token::Eof, Span { start: 20, end: 20, line: 2, column: 9 }),
+    ];
+
+    let mut lexer = lexer::new(source_code);
+    for (expected_token, expected_span) in expected_spans {
+        let spanned_token = lexer.next_token().unwrap();
+        assert_eq!(expected_token, spanned_token.token);
+        assert_eq!(expected_span, spanned_token.span);
+    }
+}
+
+#[test]
+fn test_lexer_string_literals() {
+    let source_code = r#""foobar" "foo bar" "tab\there" "quote \" and \\ slash" "line\nbreak" "\u{1F600}" """#.into();
+
+    let expected_tokens = [
+        Token::Str { literal: "foobar".into() },
+        Token::Str { literal: "foo bar".into() },
+        Token::Str { literal: "tab\there".into() },
+        Token::Str { literal: "quote \" and \\ slash".into() },
+        Token::Str { literal: "line\nbreak".into() },
+        Token::Str { literal: "\u{1F600}".into() },
+        Token::Str { literal: "".into() },
+        Token::Eof,
+    ];
+
+    let mut lexer = lexer::new(source_code);
+    for expected_token in expected_tokens {
+        let token = lexer.next_token().unwrap().token;
+        assert_eq!(expected_token, token)
+    }
+}
+
+#[test]
+fn test_lexer_string_errors() {
+    struct LexErrorTestCase {
+        source_code: String,
+        expected_error: LexError,
+    }
+
+    let test_cases = vec![
+        LexErrorTestCase {
+            source_code: "let s = \"unterminated;".into(),
+            expected_error: LexError::UnterminatedString {
+                span: Span { start: 8, end: 22, line: 1, column: 9 },
+            },
+        },
+        LexErrorTestCase {
+            source_code: r#""bad \q escape""#.into(),
+            expected_error: LexError::InvalidEscape {
+                sequence: r"\q".into(),
+                span: Span { start: 5, end: 7, line: 1, column: 6 },
+            },
+        },
+        LexErrorTestCase {
+            source_code: r#""\u{110000}""#.into(),
+            expected_error: LexError::InvalidEscape {
+                sequence: r"\u{110000}".into(),
+                span: Span { start: 1, end: 11, line: 1, column: 2 },
+            },
+        },
+    ];
+
+    for test_case in test_cases {
+        let mut lexer = lexer::new(test_case.source_code);
+        let error = loop {
+            match lexer.next_token() {
+                Ok(spanned_token) if spanned_token.token == Token::Eof => panic!("expected a lexer error"),
+                Ok(_) => continue,
+                Err(error) => break error,
+            }
+        };
+        assert_eq!(test_case.expected_error, error);
+    }
+}
+
+#[test]
+fn test_lexer_resumes_after_invalid_escape() {
+    let mut lexer = lexer::new(r#""\q" 5"#.into());
+    assert!(lexer.next_token().is_err());
+    assert_eq!(Token::Int { literal: "5".into() }, lexer.next_token().unwrap().token);
+}
+
+#[test]
+fn test_lexer_square_brackets() {
+    let source_code = "[1, 2][0]".into();
+
+    let expected_tokens = [
+        Token::LeftSquare,
+        Token::Int { literal: "1".into() },
+        Token::Comma,
+        Token::Int { literal: "2".into() },
+        Token::RightSquare,
+        Token::LeftSquare,
+        Token::Int { literal: "0".into() },
+        Token::RightSquare,
+        Token::Eof,
+    ];
+
+    let mut lexer = lexer::new(source_code);
+    for expected_token in expected_tokens {
+        let token = lexer.next_token().unwrap().token;
+        assert_eq!(expected_token, token)
+    }
+}
+
+#[test]
+fn test_lexer_colon() {
+    let source_code = "{\"foo\": \"bar\"}".into();
+
+    let expected_tokens = [
+        Token::LeftBracket,
+        Token::Str { literal: "foo".into() },
+        Token::Colon,
+        Token::Str { literal: "bar".into() },
+        Token::RightBracket,
+        Token::Eof,
+    ];
+
+    let mut lexer = lexer::new(source_code);
+    for expected_token in expected_tokens {
+        let token = lexer.next_token().unwrap().token;
+        assert_eq!(expected_token, token)
+    }
+}
+
+#[test]
+fn test_lexer_unicode_identifiers_and_strings() {
+    let source_code = "let café = \"🐒 ok\";\nlet _über2 = 変数".into();
+
+    let expected_tokens = [
+        (Token::Let, Span { start: 0, end: 3, line: 1, column: 1 }),
+        (Token::Identifier { literal: "café".into() }, Span { start: 4, end: 9, line: 1, column: 5 }),
+        (Token::Assign, Span { start: 10, end: 11, line: 1, column: 10 }),
+        (Token::Str { literal: "🐒 ok".into() }, Span { start: 12, end: 21, line: 1, column: 12 }),
+        (Token::Semicolon, Span { start: 21, end: 22, line: 1, column: 18 }),
+        (Token::Let, Span { start: 23, end: 26, line: 2, column: 1 }),
+        (Token::Identifier { literal: "_über2".into() }, Span { start: 27, end: 34, line: 2, column: 5 }),
+        (Token::Assign, Span { start: 35, end: 36, line: 2, column: 12 }),
+        (Token::Identifier { literal: "変数".into() }, Span { start: 37, end: 43, line: 2, column: 14 }),
+        (Token::Eof, Span { start: 43, end: 43, line: 2, column: 16 }),
+    ];
+
+    let mut lexer = lexer::new(source_code);
+    for (expected_token, expected_span) in expected_tokens {
+        let spanned_token = lexer.next_token().unwrap();
+        assert_eq!(expected_token, spanned_token.token);
+        assert_eq!(expected_span, spanned_token.span);
+    }
+}
+
+#[test]
+fn test_lexer_numbers_and_identifiers_are_separate_tokens() {
+    let source_code = "5abc x1 ½".into();
+
+    let expected_tokens = [
+        Token::Int { literal: "5".into() },
+        Token::Identifier { literal: "abc".into() },
+        Token::Identifier { literal: "x1".into() },
+        Token::Illegal,
+        Token::Eof,
+    ];
+
+    let mut lexer = lexer::new(source_code);
+    for expected_token in expected_tokens {
+        let token = lexer.next_token().unwrap().token;
+        assert_eq!(expected_token, token)
+    }
+}
+
+#[test]
+fn test_lexer_stops_after_eof() {
+    let mut lexer = lexer::new("x".into());
+    assert_eq!(Token::Identifier { literal: "x".into() }, lexer.next_token().unwrap().token);
+    assert_eq!(Token::Eof, lexer.next_token().unwrap().token);
+    assert_eq!(Err(LexError::EndOfInput), lexer.next_token());
+}
+
+#[test]
+fn test_lexer_skips_comments_by_default() {
+    let source_code = "
+    // leading comment
+    let x = 10 / 2; // trailing comment
+    /* block /* nested */ still comment */ x
+    // comment at end of input".into();
+
+    let expected_tokens = [
+        Token::Let,
+        Token::Identifier { literal: "x".into() },
+        Token::Assign,
+        Token::Int { literal: "10".into() },
+        Token::Slash,
+        Token::Int { literal: "2".into() },
+        Token::Semicolon,
+        Token::Identifier { literal: "x".into() },
+        Token::Eof,
+    ];
+
+    let mut lexer = lexer::new(source_code);
+    for expected_token in expected_tokens {
+        let spanned_token = lexer.next_token().unwrap();
+        assert_eq!(expected_token, spanned_token.token);
+        assert!(spanned_token.leading_trivia.is_empty());
+    }
+}
+
+#[test]
+fn test_lexer_preserves_comments_as_trivia() {
+    let source_code = "// first\n/* second\n */ x // last".into();
+    let mut lexer = lexer::new_with_options(source_code, LexerOptions { preserve_comments: true });
+
+    let identifier = lexer.next_token().unwrap();
+    assert_eq!(Token::Identifier { literal: "x".into() }, identifier.token);
+    assert_eq!(Span { start: 23, end: 24, line: 3, column: 5 }, identifier.span);
+    assert_eq!(vec![
+        Trivia {
+            kind: TriviaKind::LineComment,
+            text: "// first".into(),
+            span: Span { start: 0, end: 8, line: 1, column: 1 },
+        },
+        Trivia {
+            kind: TriviaKind::BlockComment,
+            text: "/* second\n */".into(),
+            span: Span { start: 9, end: 22, line: 2, column: 1 },
+        },
+    ], identifier.leading_trivia);
+
+    let eof = lexer.next_token().unwrap();
+    assert_eq!(Token::Eof, eof.token);
+    assert_eq!(vec![
+        Trivia {
+            kind: TriviaKind::LineComment,
+            text: "// last".into(),
+            span: Span { start: 25, end: 32, line: 3, column: 7 },
+        },
+    ], eof.leading_trivia);
+}
+
+#[test]
+fn test_lexer_unterminated_block_comment() {
+    let mut lexer = lexer::new("x /* open /* nested */".into());
+    assert_eq!(Token::Identifier { literal: "x".into() }, lexer.next_token().unwrap().token);
+    assert_eq!(
+        Err(LexError::UnterminatedComment { span: Span { start: 2, end: 22, line: 1, column: 3 } }),
+        lexer.next_token()
+    );
+    assert_eq!(Token::Eof, lexer.next_token().unwrap().token);
+}
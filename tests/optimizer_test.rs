@@ -0,0 +1,76 @@
+use tarzan::{lexer, optimizer, parser, serialize};
+use tarzan::ast::Program;
+use tarzan::optimizer::Warning;
+use tarzan::token::Span;
+
+fn parse(source_code: &str) -> Program {
+    let mut parser = parser::new(lexer::new(source_code.into()));
+    return parser.parse().unwrap_or_else(|errors| panic!("found parser errors: {:?}", errors));
+}
+
+fn optimize(source_code: &str) -> (String, Vec<Warning>) {
+    let (program, warnings) = optimizer::optimize(parse(source_code));
+    return (serialize::sexpr::program(&program), warnings);
+}
+
+#[test]
+fn test_fold_constants() {
+    let test_cases = [
+        ("1 + 2 * 3", "7"),
+        ("(1 + 2) * 3 - -4 / 2", "11"),
+        ("+5", "5"),
+        ("1 < 2", "true"),
+        ("2 * 2 == 4 != false", "true"),
+        ("!true", "false"),
+        ("!!false", "false"),
+        ("!5", "false"),
+        ("true == !false", "true"),
+        ("x + 1 * 2", "(+ x 2)"),
+        ("fn(a) { a * (2 + 3) }", "(fn (a) (block (* a 5)))"),
+        ("[1 + 1, {2 * 2: -(3)}][0]", "(index (array 2 (hash (4 -3))) 0)"),
+        // Operations that fail at runtime are left for the runtime to report.
+        ("9223372036854775807 + 1", "(+ 9223372036854775807 1)"),
+        ("1 + true", "(+ 1 true)"),
+        ("true > false", "(> true false)"),
+        ("-true", "(- true)"),
+        ("\"a\" + \"b\"", "(+ \"a\" \"b\")"),
+    ];
+    for (source_code, expected) in test_cases {
+        let (optimized, warnings) = optimize(source_code);
+        assert_eq!(format!("(program {})", expected), optimized, "source code: {}", source_code);
+        assert!(warnings.is_empty(), "source code: {}", source_code);
+    }
+}
+
+#[test]
+fn test_eliminate_dead_branches() {
+    let test_cases = [
+        ("if (true) { a } else { b }", "(if true (block a) nil)"),
+        ("if (1 > 2) { a } else { b }", "(if false nil (block b))"),
+        ("if (false) { a }", "(if false nil nil)"),
+        ("if (0) { a } else { b }", "(if 0 (block a) nil)"),
+        ("if (x) { 1 + 1 } else { 2 + 2 }", "(if x (block 2) (block 4))"),
+        ("if (false) { let a = 1 + 1; }", "(if false (block (let a 2)) nil)"),
+        ("if (true) { a } else { let b = 1; }", "(if true (block a) (block (let b 1)))"),
+    ];
+    for (source_code, expected) in test_cases {
+        assert_eq!(format!("(program {})", expected), optimize(source_code).0, "source code: {}", source_code);
+    }
+}
+
+#[test]
+fn test_report_division_by_zero() {
+    let (optimized, warnings) = optimize("let f = fn(x) {\n  x / (1 - 1)\n};\n10 / 0");
+    assert_eq!("(program (let f (fn (x) (block (/ x 0)))) (/ 10 0))", optimized);
+    assert_eq!(
+        vec![
+            Warning::DivisionByZero { span: Span { start: 18, end: 28, line: 2, column: 3 } },
+            Warning::DivisionByZero { span: Span { start: 33, end: 39, line: 4, column: 1 } },
+        ],
+        warnings
+    );
+    assert_eq!("division by zero at 2:3", warnings[0].to_string());
+
+    let (_, warnings) = optimize("if (false) { 1 / 0 } else { 2 / 1 }");
+    assert!(warnings.is_empty(), "{:?}", warnings);
+}
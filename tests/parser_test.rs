@@ -1,7 +1,10 @@
 use tarzan::{lexer, parser};
 use tarzan::ast::{Statement, Expression, Program};
-use tarzan::parser::Parser;
-use tarzan::token::Token;
+use tarzan::ast::printer::Print;
+use tarzan::parser::error::ParseError;
+use tarzan::parser::operator::{self, Associativity, InfixOperator, INFIX_OPERATORS};
+use tarzan::parser::Precedence;
+use tarzan::token::{Span, Token};
 
 #[test]
 fn test_let_statements() {
@@ -14,15 +17,13 @@ fn test_let_statements() {
     let program = parse(source_code);
     assert_eq!(3, program.statements.len());
 
-    let expected_literals = vec!["x", "y", "foobar"];
-
-    for i in 0..3 {
-        let expected_literal = expected_literals[i];
-        let statement = program.statements.get(i).unwrap();
+    let expected_literals = [("x", "5"), ("y", "10"), ("foobar", "838383")];
 
+    for (statement, (expected_literal, expected_value)) in program.statements.iter().zip(expected_literals) {
         match statement {
             Statement::Let(let_statement) => {
                 assert_eq!(expected_literal, let_statement.identifier_name);
+                assert_is_integer_expression(expected_value.into(), &let_statement.value);
             }
             _ => panic!("statement is not a let statement, got: {}", statement)
         }
@@ -32,9 +33,24 @@ fn test_let_statements() {
 fn parse(source_code: String) -> Program {
     let lexer = lexer::new(source_code);
     let mut parser = parser::new(lexer);
-    let program = parser.parse().unwrap();
-    assert_zero_parser_errors(&parser);
-    return program;
+    return parser.parse().unwrap_or_else(|errors| panic_with_parser_errors(&errors));
+}
+
+/// The statement as source text with every expression in `Debug` form, so that tests can check the
+/// exact tree the parser built.
+fn debug_string(statement: &Statement) -> String {
+    match statement {
+        Statement::Let(let_statement) => format!("let {} = {:?};", let_statement.identifier_name, let_statement.value),
+        Statement::Return(return_statement) => format!("return {:?};", return_statement.value),
+        Statement::Expression(expression) => format!("{:?}", expression),
+        Statement::Error(_) => statement.to_string(),
+    }
+}
+
+fn parse_errors(source_code: String) -> Vec<ParseError> {
+    let lexer = lexer::new(source_code);
+    let mut parser = parser::new(lexer);
+    return parser.parse().expect_err("expected parser errors");
 }
 
 #[test]
@@ -48,40 +64,251 @@ fn test_return_statements() {
     let program = parse(source_code);
     assert_eq!(3, program.statements.len());
 
-    let expected_literals = vec!["5", "10", "993322"];
-
-    for i in 0..3 {
-        let expected_literal = expected_literals[i];
-        let statement = program.statements.get(i).unwrap();
+    let expected_literals = ["5", "10", "993322"];
 
+    for (statement, expected_literal) in program.statements.iter().zip(expected_literals) {
         match statement {
-            Statement::Return(_) => {}
+            Statement::Return(return_statement) => assert_is_integer_expression(expected_literal.into(), &return_statement.value),
             _ => panic!("statement is not a return statement, got: {}", statement)
         }
     };
 }
 
+#[test]
+fn test_let_and_return_statements_with_complex_expressions() {
+    struct StatementTestCase {
+        source_code: String,
+        expected_statement_string: String,
+    }
+
+    let test_cases = vec![
+        StatementTestCase {
+            source_code: "let x = 1 + y;".into(),
+            expected_statement_string: "let x = InfixExpression { \
+            operator: \"+\", \
+            left: IntegerLiteral { token: Int { literal: \"1\" }, value: 1 }, \
+            right: Identifier { token: Identifier { literal: \"y\" } } };".into(),
+        },
+        StatementTestCase {
+            source_code: "return if (a) { b };".into(),
+            expected_statement_string: "return IfExpression { \
+            token: If, \
+            condition: Identifier { token: Identifier { literal: \"a\" } }, \
+            consequence: Some(BlockStatement { statements: [Expression(Identifier { token: Identifier { literal: \"b\" } })] }), \
+            alternative: None };".into(),
+        },
+    ];
+
+    for test_case in test_cases {
+        let program = parse(test_case.source_code);
+        assert_eq!(1, program.statements.len());
+        let first_statement = program.statements.first().unwrap();
+        assert_eq!(test_case.expected_statement_string, without_spans(debug_string(first_statement)));
+    }
+}
+
+#[test]
+fn test_statements_without_trailing_semicolons() {
+    let source_code = "
+        let x = 5
+        return x
+        x + 1".into();
+
+    let program = parse(source_code);
+    assert_eq!(3, program.statements.len());
+
+    match program.statements.first().unwrap() {
+        Statement::Let(let_statement) => assert_is_integer_expression("5".into(), &let_statement.value),
+        statement => panic!("statement is not a let statement, got: {}", statement)
+    }
+}
+
+#[test]
+fn test_incomplete_let_statement_terminates_with_error() {
+    let errors = parse_errors("let x =".into());
+    assert_eq!(vec![ParseError::NoPrefixParser {
+        token: Token::Eof,
+        span: Span { start: 7, end: 7, line: 1, column: 8 },
+    }], errors);
+}
+
+#[test]
+fn test_parse_errors() {
+    struct ParseErrorTestCase {
+        source_code: String,
+        expected_error: ParseError,
+    }
+
+    let test_cases = vec![
+        ParseErrorTestCase {
+            source_code: "let = 5;".into(),
+            expected_error: ParseError::UnexpectedToken {
+                expected: "identifier",
+                found: Token::Assign,
+                span: Span { start: 4, end: 5, line: 1, column: 5 },
+            },
+        },
+        ParseErrorTestCase {
+            source_code: "let x 5;".into(),
+            expected_error: ParseError::UnexpectedToken {
+                expected: "`=`",
+                found: Token::Int { literal: "5".into() },
+                span: Span { start: 6, end: 7, line: 1, column: 7 },
+            },
+        },
+        ParseErrorTestCase {
+            source_code: "let x = 99999999999999999999;".into(),
+            expected_error: ParseError::IntegerOverflow {
+                literal: "99999999999999999999".into(),
+                span: Span { start: 8, end: 28, line: 1, column: 9 },
+            },
+        },
+        ParseErrorTestCase {
+            source_code: "(1 + 2".into(),
+            expected_error: ParseError::UnclosedDelimiter {
+                delimiter: Token::LeftParenthesis,
+                span: Span { start: 0, end: 1, line: 1, column: 1 },
+            },
+        },
+        ParseErrorTestCase {
+            source_code: "if (x) {\n  y".into(),
+            expected_error: ParseError::UnclosedDelimiter {
+                delimiter: Token::LeftBracket,
+                span: Span { start: 7, end: 8, line: 1, column: 8 },
+            },
+        },
+        ParseErrorTestCase {
+            source_code: "fn(x, 1) {}".into(),
+            expected_error: ParseError::UnexpectedToken {
+                expected: "identifier",
+                found: Token::Int { literal: "1".into() },
+                span: Span { start: 6, end: 7, line: 1, column: 7 },
+            },
+        },
+    ];
+
+    for test_case in test_cases {
+        let errors = parse_errors(test_case.source_code);
+        assert_eq!(Some(&test_case.expected_error), errors.first());
+    }
+}
+
+fn parse_with_recovery(source_code: &str) -> (Program, Vec<ParseError>) {
+    let mut parser = parser::new(lexer::new(source_code.into()));
+    return parser.parse_with_recovery();
+}
+
+#[test]
+fn test_error_recovery_reports_one_error_per_mistake() {
+    let test_cases = [
+        ("let = 5; let y = 10;", 1),
+        ("let x 5; let y = 10; y", 1),
+        ("let x = 1 +; let y = 2 *; x", 2),
+        ("if (x { y } let z = 1;", 1),
+        ("let f = fn() { let = 1; return 2; }; f()", 1),
+        ("let f = fn(x) { x + }; let g = 1;", 1),
+        ("add(1, ; let y = 2;", 1),
+        ("} let y = 2;", 1),
+        ("let h = {1: }; let a = [1, ]; let b = 3;", 2),
+    ];
+
+    for (source_code, expected_error_count) in test_cases {
+        let (_, errors) = parse_with_recovery(source_code);
+        let messages: Vec<String> = errors.iter().map(|error| error.to_string()).collect();
+        assert_eq!(expected_error_count, errors.len(), "source: {}, errors: {:?}", source_code, messages);
+    }
+}
+
+#[test]
+fn test_error_recovery_keeps_rest_of_program() {
+    let (program, errors) = parse_with_recovery("let x = 1 +; 5 5; let f = fn() { let = 1; return 2; }; f()");
+    assert_eq!(2, errors.len());
+    assert_eq!("\
+let x = <error>;
+5;
+5;
+let f = fn() {
+    <error>;
+    return 2;
+};
+f();", program.to_string());
+
+    match &program.statements[0] {
+        Statement::Let(let_statement) => assert_eq!(
+            Expression::Error { span: Span { start: 8, end: 12, line: 1, column: 9 } },
+            let_statement.value,
+        ),
+        statement => panic!("statement is not a let statement, got: {}", statement)
+    }
+}
+
+#[test]
+fn test_error_recovery_statement_spans() {
+    let (program, _) = parse_with_recovery("let = 5;\nx");
+    assert_eq!(Statement::Error(Span { start: 0, end: 8, line: 1, column: 1 }), program.statements[0]);
+    assert_eq!(2, program.statements.len());
+}
+
+#[test]
+fn test_parse_error_display() {
+    let errors = parse_errors("let x 5;".into());
+    assert_eq!("expected `=`, found `5` at 1:7", errors[0].to_string());
+}
+
+#[test]
+fn test_comments_are_ignored_by_parser() {
+    let source_code = "
+        // configuration
+        let x = 5; /* the answer
+        is not 42 */
+        return x; // done".into();
+
+    let program = parse(source_code);
+    assert_eq!(2, program.statements.len());
+}
+
 #[test]
 fn test_identifier_expression() {
     let source_code = "foobar;".into();
     let program = parse(source_code);
     assert_eq!(1, program.statements.len());
 
-    let first_statement = program.statements.get(0).unwrap();
-    if let Statement::Expression(Expression::Identifier(token)) = first_statement {
+    let first_statement = program.statements.first().unwrap();
+    if let Statement::Expression(Expression::Identifier { token, .. }) = first_statement {
         assert_eq!(token, &Token::Identifier { literal: "foobar".into() })
     } else {
         panic!("statement is not an expression containing an identifier, got: {}", first_statement)
     }
 }
 
+#[test]
+fn test_string_literal_expression() {
+    let program = parse("\"hello world\";".into());
+    assert_eq!(1, program.statements.len());
+
+    let first_statement = program.statements.first().unwrap();
+    if let Statement::Expression(Expression::StringLiteral { value, span, .. }) = first_statement {
+        assert_eq!("hello world", value);
+        assert_eq!(Span { start: 0, end: 13, line: 1, column: 1 }, *span);
+    } else {
+        panic!("statement is not an expression containing a string literal, got: {}", first_statement)
+    }
+}
+
+#[test]
+fn test_unterminated_string_reports_single_error() {
+    let errors = parse_errors("let s = \"abc".into());
+    assert_eq!(1, errors.len());
+    assert_eq!("unterminated string literal at 1:9", errors[0].to_string());
+}
+
 #[test]
 fn test_integer_literal_expression() {
     let source_code = "5;".into();
     let program = parse(source_code);
     assert_eq!(1, program.statements.len());
 
-    let first_statement = program.statements.get(0).unwrap();
+    let first_statement = program.statements.first().unwrap();
     if let Statement::Expression(expression) = first_statement {
         assert_is_integer_expression("5".into(), expression);
     } else {
@@ -92,7 +319,7 @@ fn test_integer_literal_expression() {
 fn assert_is_integer_expression(expected_literal: String, expression: &Expression) {
     let expected_value = expected_literal.parse::<i64>().unwrap();
 
-    if let Expression::IntegerLiteral { token, value } = expression {
+    if let Expression::IntegerLiteral { token, value, .. } = expression {
         assert_eq!(&Token::Int { literal: expected_literal }, token);
         assert_eq!(&expected_value, value);
     } else {
@@ -125,8 +352,8 @@ fn test_prefix_expressions() {
         let program = parse(test_case.source_code);
         assert_eq!(1, program.statements.len());
 
-        let first_statement = program.statements.get(0).unwrap();
-        if let Statement::Expression(Expression::PrefixExpression { right: expression, operator }) = first_statement {
+        let first_statement = program.statements.first().unwrap();
+        if let Statement::Expression(Expression::PrefixExpression { right: expression, operator, .. }) = first_statement {
             assert_eq!(&test_case.operator, operator);
             assert_is_integer_expression(test_case.expected_literal, expression);
         } else {
@@ -160,8 +387,8 @@ fn test_prefix_boolean_expressions() {
         let program = parse(test_case.source_code);
         assert_eq!(1, program.statements.len());
 
-        let first_statement = program.statements.get(0).unwrap();
-        if let Statement::Expression(Expression::PrefixExpression { right: expression, operator }) = first_statement {
+        let first_statement = program.statements.first().unwrap();
+        if let Statement::Expression(Expression::PrefixExpression { right: expression, operator, .. }) = first_statement {
             assert_eq!(&test_case.operator, operator);
             assert_is_boolean_expression(test_case.expected_token, expression);
         } else {
@@ -294,8 +521,8 @@ fn test_infix_expressions() {
         let program = parse(test_case.source_code);
         assert_eq!(1, program.statements.len());
 
-        let first_statement = program.statements.get(0).unwrap();
-        assert_eq!(test_case.expected_expression_string, first_statement.to_string());
+        let first_statement = program.statements.first().unwrap();
+        assert_eq!(test_case.expected_expression_string, without_spans(debug_string(first_statement)));
     }
 }
 
@@ -336,11 +563,94 @@ fn test_infix_expressions_with_3_operands() {
         let program = parse(test_case.source_code);
         assert_eq!(1, program.statements.len());
 
-        let first_statement = program.statements.get(0).unwrap();
-        assert_eq!(test_case.expected_expression_string, first_statement.to_string());
+        let first_statement = program.statements.first().unwrap();
+        assert_eq!(test_case.expected_expression_string, without_spans(debug_string(first_statement)));
+    }
+}
+
+#[test]
+fn test_operator_precedence() {
+    let test_cases = [
+        ("-a * b", "((-a) * b);"),
+        ("a * -b", "(a * (-b));"),
+        ("-a / b * c", "(((-a) / b) * c);"),
+        ("a - b - c", "((a - b) - c);"),
+        ("!a == b / -c", "((!a) == (b / (-c)));"),
+        ("!-a", "(!(-a));"),
+        ("a + b + c", "((a + b) + c);"),
+        ("a + b - c", "((a + b) - c);"),
+        ("a * b * c", "((a * b) * c);"),
+        ("a * b / c", "((a * b) / c);"),
+        ("a + b / c", "(a + (b / c));"),
+        ("a + b * c + d / e - f", "(((a + (b * c)) + (d / e)) - f);"),
+        ("5 > 4 == 3 < 4", "((5 > 4) == (3 < 4));"),
+        ("3 + 4 * 5 == 3 * 1 + 4 * 5", "((3 + (4 * 5)) == ((3 * 1) + (4 * 5)));"),
+        ("1 + (2 + 3) + 4", "((1 + (2 + 3)) + 4);"),
+        ("-(5 + 5)", "(-(5 + 5));"),
+        ("a + add(b * c) + d", "((a + add((b * c))) + d);"),
+        ("add(a + b + c * d / f + g)", "add((((a + b) + ((c * d) / f)) + g));"),
+        ("a * [1, 2, 3, 4][b * c] * d", "((a * ([1, 2, 3, 4][(b * c)])) * d);"),
+        ("add(a * b[2], b[1], 2 * [1, 2][1])", "add((a * (b[2])), (b[1]), (2 * ([1, 2][1])));"),
+    ];
+
+    for (source_code, expected) in test_cases {
+        let program = parse(source_code.into());
+        assert_eq!(expected, program.parenthesized().to_string(), "source: {}", source_code);
     }
 }
 
+#[test]
+fn test_operator_table() {
+    assert_eq!(Precedence::Product, operator::infix_operator("*").unwrap().precedence);
+    assert_eq!(Precedence::Product, operator::infix_operator("/").unwrap().precedence);
+    assert_eq!(Precedence::Sum, operator::infix_operator("-").unwrap().precedence);
+    assert!(operator::infix_operator("!").is_none());
+
+    let minus = operator::infix_operator("-").unwrap();
+    assert_eq!(Associativity::Left, minus.associativity);
+    assert_eq!(Precedence::Sum.value(), minus.right_binding_power());
+
+    let power = InfixOperator {
+        token: Token::Asterisk,
+        symbol: "^",
+        precedence: Precedence::Product,
+        associativity: Associativity::Right,
+        parse: |_, left| Some(left),
+    };
+    assert_eq!(Precedence::Product.value() - 1, power.right_binding_power());
+
+    assert_eq!(Precedence::Prefix, operator::prefix_operator("-").unwrap().precedence);
+    assert_eq!(Precedence::Prefix, operator::prefix_operator("!").unwrap().precedence);
+    assert!(operator::prefix_operator("*").is_none());
+}
+
+/// The language's operators with `-` made right-associative and binding more tightly than `*`.
+static RIGHT_MINUS_OPERATORS: &[InfixOperator] = &[
+    operator::binary(Token::Plus, "+", Precedence::Sum),
+    operator::binary(Token::Asterisk, "*", Precedence::Sum),
+    operator::right_binary(Token::Minus, "-", Precedence::Product),
+];
+
+#[test]
+fn test_right_associative_operator_parsing() {
+    let test_cases = [
+        ("1 - 2 - 3", "(1 - (2 - 3));"),
+        ("1 - 2 - 3 - 4", "(1 - (2 - (3 - 4)));"),
+        ("1 + 2 - 3 - 4 + 5", "((1 + (2 - (3 - 4))) + 5);"),
+        ("1 - 2 * 3 - 4", "((1 - 2) * (3 - 4));"),
+        ("-1 - -2 - 3", "((-1) - ((-2) - 3));"),
+    ];
+
+    for (source_code, expected) in test_cases {
+        let mut parser = parser::with_infix_operators(lexer::new(source_code.into()), RIGHT_MINUS_OPERATORS);
+        let program = parser.parse().unwrap_or_else(|errors| panic_with_parser_errors(&errors));
+        assert_eq!(expected, program.parenthesized().to_string(), "source: {}", source_code);
+    }
+
+    let mut parser = parser::with_infix_operators(lexer::new("1 - 2 - 3".into()), INFIX_OPERATORS);
+    assert_eq!("((1 - 2) - 3);", parser.parse().unwrap().parenthesized().to_string());
+}
+
 #[test]
 fn test_boolean_parsing() {
     struct BooleanStatementTestCase {
@@ -370,8 +680,8 @@ fn test_boolean_parsing() {
         let program = parse(test_case.source_code);
         assert_eq!(1, program.statements.len());
 
-        let first_statement = program.statements.get(0).unwrap();
-        assert_eq!(test_case.expected_expression_string, first_statement.to_string());
+        let first_statement = program.statements.first().unwrap();
+        assert_eq!(test_case.expected_expression_string, without_spans(debug_string(first_statement)));
     }
 }
 
@@ -388,8 +698,8 @@ fn test_if_expression_parsing() {
             expected_expression_string: "\
             IfExpression { \
                 token: If, \
-                condition: InfixExpression { operator: \"<\", left: Identifier(Identifier { literal: \"x\" }), right: Identifier(Identifier { literal: \"y\" }) }, \
-                consequence: Some(BlockStatement { statements: [Expression(Identifier(Identifier { literal: \"x\" }))] }), \
+                condition: InfixExpression { operator: \"<\", left: Identifier { token: Identifier { literal: \"x\" } }, right: Identifier { token: Identifier { literal: \"y\" } } }, \
+                consequence: Some(BlockStatement { statements: [Expression(Identifier { token: Identifier { literal: \"x\" } })] }), \
                 alternative: None \
             }".into(),
         },
@@ -398,9 +708,9 @@ fn test_if_expression_parsing() {
             expected_expression_string: "\
             IfExpression { \
                 token: If, \
-                condition: InfixExpression { operator: \"<\", left: Identifier(Identifier { literal: \"x\" }), right: Identifier(Identifier { literal: \"y\" }) }, \
-                consequence: Some(BlockStatement { statements: [Expression(Identifier(Identifier { literal: \"x\" }))] }), \
-                alternative: Some(BlockStatement { statements: [Expression(Identifier(Identifier { literal: \"y\" }))] }) \
+                condition: InfixExpression { operator: \"<\", left: Identifier { token: Identifier { literal: \"x\" } }, right: Identifier { token: Identifier { literal: \"y\" } } }, \
+                consequence: Some(BlockStatement { statements: [Expression(Identifier { token: Identifier { literal: \"x\" } })] }), \
+                alternative: Some(BlockStatement { statements: [Expression(Identifier { token: Identifier { literal: \"y\" } })] }) \
             }".into(),
         },
     ];
@@ -409,8 +719,8 @@ fn test_if_expression_parsing() {
     for test_case in test_cases {
         let program = parse(test_case.source_code);
         assert_eq!(1, program.statements.len());
-        let first_statement = program.statements.get(0).unwrap();
-        assert_eq!(test_case.expected_expression_string, first_statement.to_string());
+        let first_statement = program.statements.first().unwrap();
+        assert_eq!(test_case.expected_expression_string, without_spans(debug_string(first_statement)));
     }
 }
 
@@ -434,7 +744,7 @@ fn test_function_literal_parsing() {
             expected_expression_string: "Function { \
             token: Function, \
             parameters: [\
-            Identifier(Identifier { literal: \"x\" })], \
+            Identifier { token: Identifier { literal: \"x\" } }], \
             body: BlockStatement { statements: [] } }".into(),
         },
         FunctionLiteralTestCase {
@@ -442,8 +752,8 @@ fn test_function_literal_parsing() {
             expected_expression_string: "Function { \
             token: Function, \
             parameters: [\
-            Identifier(Identifier { literal: \"x\" }), \
-            Identifier(Identifier { literal: \"y\" })], \
+            Identifier { token: Identifier { literal: \"x\" } }, \
+            Identifier { token: Identifier { literal: \"y\" } }], \
             body: BlockStatement { statements: [] } }".into(),
         },
         FunctionLiteralTestCase {
@@ -451,26 +761,255 @@ fn test_function_literal_parsing() {
             expected_expression_string: "Function { \
             token: Function, \
             parameters: [\
-            Identifier(Identifier { literal: \"x\" }), \
-            Identifier(Identifier { literal: \"y\" })], \
-            body: BlockStatement { statements: [Expression(InfixExpression { operator: \"+\", left: Identifier(Identifier { literal: \"x\" }), right: Identifier(Identifier { literal: \"y\" }) })] } }".into(),
+            Identifier { token: Identifier { literal: \"x\" } }, \
+            Identifier { token: Identifier { literal: \"y\" } }], \
+            body: BlockStatement { statements: [Expression(InfixExpression { operator: \"+\", left: Identifier { token: Identifier { literal: \"x\" } }, right: Identifier { token: Identifier { literal: \"y\" } } })] } }".into(),
+        },
+    ];
+
+
+    for test_case in test_cases {
+        let program = parse(test_case.source_code);
+        assert_eq!(1, program.statements.len());
+        let first_statement = program.statements.first().unwrap();
+        assert_eq!(test_case.expected_expression_string, without_spans(debug_string(first_statement)));
+    }
+}
+
+#[test]
+fn test_call_expression_parsing() {
+    struct CallExpressionTestCase {
+        source_code: String,
+        expected_expression_string: String,
+    }
+
+    let test_cases = vec![
+        CallExpressionTestCase {
+            source_code: "add();".into(),
+            expected_expression_string: "Call { \
+            function: Identifier { token: Identifier { literal: \"add\" } }, \
+            arguments: [] }".into(),
+        },
+        CallExpressionTestCase {
+            source_code: "add(1, 2 * 3, x);".into(),
+            expected_expression_string: "Call { \
+            function: Identifier { token: Identifier { literal: \"add\" } }, \
+            arguments: [\
+            IntegerLiteral { token: Int { literal: \"1\" }, value: 1 }, \
+            InfixExpression { operator: \"*\", \
+                left: IntegerLiteral { token: Int { literal: \"2\" }, value: 2 }, \
+                right: IntegerLiteral { token: Int { literal: \"3\" }, value: 3 } }, \
+            Identifier { token: Identifier { literal: \"x\" } }] }".into(),
+        },
+        CallExpressionTestCase {
+            source_code: "a + add(b) * c;".into(),
+            expected_expression_string: "InfixExpression { \
+            operator: \"+\", \
+            left: Identifier { token: Identifier { literal: \"a\" } }, \
+            right: InfixExpression { operator: \"*\", \
+                left: Call { \
+                    function: Identifier { token: Identifier { literal: \"add\" } }, \
+                    arguments: [Identifier { token: Identifier { literal: \"b\" } }] }, \
+                right: Identifier { token: Identifier { literal: \"c\" } } } }".into(),
+        },
+        CallExpressionTestCase {
+            source_code: "fn(x) { x }(5)".into(),
+            expected_expression_string: "Call { \
+            function: Function { \
+                token: Function, \
+                parameters: [Identifier { token: Identifier { literal: \"x\" } }], \
+                body: BlockStatement { statements: [Expression(Identifier { token: Identifier { literal: \"x\" } })] } }, \
+            arguments: [IntegerLiteral { token: Int { literal: \"5\" }, value: 5 }] }".into(),
+        },
+    ];
+
+    for test_case in test_cases {
+        let program = parse(test_case.source_code);
+        assert_eq!(1, program.statements.len());
+        let first_statement = program.statements.first().unwrap();
+        assert_eq!(test_case.expected_expression_string, without_spans(debug_string(first_statement)));
+    }
+}
+
+#[test]
+fn test_call_expression_with_unclosed_argument_list() {
+    let errors = parse_errors("add(1, 2".into());
+    assert_eq!(Some(&ParseError::UnexpectedToken {
+        expected: "`,` or `)`",
+        found: Token::Eof,
+        span: Span { start: 8, end: 8, line: 1, column: 9 },
+    }), errors.first());
+}
+
+#[test]
+fn test_array_literal_and_index_parsing() {
+    struct ArrayTestCase {
+        source_code: String,
+        expected_expression_string: String,
+    }
+
+    let test_cases = vec![
+        ArrayTestCase {
+            source_code: "[]".into(),
+            expected_expression_string: "ArrayLiteral { elements: [] }".into(),
+        },
+        ArrayTestCase {
+            source_code: "[1, 2 * 2]".into(),
+            expected_expression_string: "ArrayLiteral { elements: [\
+            IntegerLiteral { token: Int { literal: \"1\" }, value: 1 }, \
+            InfixExpression { operator: \"*\", \
+                left: IntegerLiteral { token: Int { literal: \"2\" }, value: 2 }, \
+                right: IntegerLiteral { token: Int { literal: \"2\" }, value: 2 } }] }".into(),
+        },
+        ArrayTestCase {
+            source_code: "myArray[1 + 1]".into(),
+            expected_expression_string: "Index { \
+            left: Identifier { token: Identifier { literal: \"myArray\" } }, \
+            index: InfixExpression { operator: \"+\", \
+                left: IntegerLiteral { token: Int { literal: \"1\" }, value: 1 }, \
+                right: IntegerLiteral { token: Int { literal: \"1\" }, value: 1 } } }".into(),
+        },
+        ArrayTestCase {
+            source_code: "a * b[2]".into(),
+            expected_expression_string: "InfixExpression { \
+            operator: \"*\", \
+            left: Identifier { token: Identifier { literal: \"a\" } }, \
+            right: Index { \
+                left: Identifier { token: Identifier { literal: \"b\" } }, \
+                index: IntegerLiteral { token: Int { literal: \"2\" }, value: 2 } } }".into(),
+        },
+        ArrayTestCase {
+            source_code: "f(x)[0]".into(),
+            expected_expression_string: "Index { \
+            left: Call { \
+                function: Identifier { token: Identifier { literal: \"f\" } }, \
+                arguments: [Identifier { token: Identifier { literal: \"x\" } }] }, \
+            index: IntegerLiteral { token: Int { literal: \"0\" }, value: 0 } }".into(),
         },
     ];
 
+    for test_case in test_cases {
+        let program = parse(test_case.source_code);
+        assert_eq!(1, program.statements.len());
+        let first_statement = program.statements.first().unwrap();
+        assert_eq!(test_case.expected_expression_string, without_spans(debug_string(first_statement)));
+    }
+}
+
+#[test]
+fn test_hash_literal_parsing() {
+    struct HashLiteralTestCase {
+        source_code: String,
+        expected_expression_string: String,
+    }
+
+    let test_cases = vec![
+        HashLiteralTestCase {
+            source_code: "{}".into(),
+            expected_expression_string: "HashLiteral { pairs: [] }".into(),
+        },
+        HashLiteralTestCase {
+            source_code: "{\"one\": 1, true: 2 * 3}".into(),
+            expected_expression_string: "HashLiteral { pairs: [\
+            (StringLiteral { token: Str { literal: \"one\" }, value: \"one\" }, \
+                IntegerLiteral { token: Int { literal: \"1\" }, value: 1 }), \
+            (Boolean { token: True, value: true }, \
+                InfixExpression { operator: \"*\", \
+                    left: IntegerLiteral { token: Int { literal: \"2\" }, value: 2 }, \
+                    right: IntegerLiteral { token: Int { literal: \"3\" }, value: 3 } })] }".into(),
+        },
+        HashLiteralTestCase {
+            source_code: "let config = {1: x};".into(),
+            expected_expression_string: "let config = HashLiteral { pairs: [\
+            (IntegerLiteral { token: Int { literal: \"1\" }, value: 1 }, \
+                Identifier { token: Identifier { literal: \"x\" } })] };".into(),
+        },
+    ];
 
     for test_case in test_cases {
         let program = parse(test_case.source_code);
         assert_eq!(1, program.statements.len());
-        let first_statement = program.statements.get(0).unwrap();
-        assert_eq!(test_case.expected_expression_string, first_statement.to_string());
+        let first_statement = program.statements.first().unwrap();
+        assert_eq!(test_case.expected_expression_string, without_spans(debug_string(first_statement)));
     }
 }
 
-fn assert_zero_parser_errors(parser: &Parser) {
-    if parser.errors.is_empty() {
-        return;
+#[test]
+fn test_block_after_if_and_fn_is_not_a_hash_literal() {
+    let program = parse("if (x) { y } else { {1: 2} }; fn() { z };".into());
+    assert_eq!(2, program.statements.len());
+
+    match program.statements.first().unwrap() {
+        Statement::Expression(Expression::IfExpression { consequence: Some(consequence), alternative: Some(alternative), .. }) => {
+            assert!(matches!(consequence.statements[0], Statement::Expression(Expression::Identifier { .. })));
+            assert!(matches!(alternative.statements[0], Statement::Expression(Expression::HashLiteral { .. })));
+        }
+        statement => panic!("statement is not an if expression, got: {}", statement)
     }
-    for err in parser.errors.iter() {
+}
+
+#[test]
+fn test_parse_error_names_source_symbols() {
+    let test_cases = [
+        ("{\"a\": 1 \"b\": 2}", "expected `,` or `}`, found `\"b\"` at 1:9"),
+        ("[1 2]", "expected `,` or `]`, found `2` at 1:4"),
+        ("add(1; 2)", "expected `,` or `)`, found `;` at 1:6"),
+        ("fn(x y) { x }", "expected `,` or `)`, found `y` at 1:6"),
+        ("if x { 1 }", "expected `(`, found `x` at 1:4"),
+        ("[1][0", "expected `]`, found end of input at 1:6"),
+    ];
+
+    for (source_code, expected) in test_cases {
+        let errors = parse_errors(source_code.into());
+        assert_eq!(expected, errors[0].to_string(), "source: {}", source_code);
+    }
+}
+
+#[test]
+fn test_hash_literal_missing_colon() {
+    let errors = parse_errors("{\"a\" 1}".into());
+    assert_eq!(Some(&ParseError::UnexpectedToken {
+        expected: "`:`",
+        found: Token::Int { literal: "1".into() },
+        span: Span { start: 5, end: 6, line: 1, column: 6 },
+    }), errors.first());
+}
+
+#[test]
+fn test_statement_and_expression_spans() {
+    let source_code = "let x = 1 + 2;\nif (x) { -x } else { x }".into();
+    let program = parse(source_code);
+    assert_eq!(2, program.statements.len());
+
+    let let_statement = &program.statements[0];
+    assert_eq!(Span { start: 0, end: 14, line: 1, column: 1 }, let_statement.span());
+    if let Statement::Let(let_statement) = let_statement {
+        assert_eq!(Span { start: 8, end: 13, line: 1, column: 9 }, let_statement.value.span());
+    } else {
+        panic!("statement is not a let statement, got: {}", let_statement);
+    }
+
+    let if_statement = &program.statements[1];
+    assert_eq!(Span { start: 15, end: 39, line: 2, column: 1 }, if_statement.span());
+    if let Statement::Expression(Expression::IfExpression { consequence: Some(consequence), .. }) = if_statement {
+        assert_eq!(Span { start: 22, end: 28, line: 2, column: 8 }, consequence.span);
+        assert_eq!(Span { start: 24, end: 26, line: 2, column: 10 }, consequence.statements[0].span());
+    } else {
+        panic!("statement is not an if expression, got: {}", if_statement);
+    }
+}
+
+fn without_spans(debug_string: String) -> String {
+    let mut result = debug_string;
+    while let Some(start) = result.find(", span: Span {") {
+        let end = start + result[start..].find('}').unwrap() + 1;
+        result.replace_range(start..end, "");
+    }
+    return result;
+}
+
+fn panic_with_parser_errors(errors: &[ParseError]) -> ! {
+    for err in errors.iter() {
         println!("found error: {}", err)
     }
     panic!()
@@ -0,0 +1,111 @@
+use tarzan::{lexer, parser};
+use tarzan::ast::Program;
+use tarzan::ast::printer::Print;
+
+fn parse(source_code: &str) -> Program {
+    let mut parser = parser::new(lexer::new(source_code.into()));
+    return parser.parse().unwrap_or_else(|errors| {
+        let messages: Vec<String> = errors.iter().map(|error| error.to_string()).collect();
+        panic!("parser errors for {:?}: {:?}", source_code, messages)
+    });
+}
+
+fn without_spans(debug_string: String) -> String {
+    let mut result = debug_string;
+    while let Some(start) = result.find(", span: Span {") {
+        let end = start + result[start..].find('}').unwrap() + 1;
+        result.replace_range(start..end, "");
+    }
+    return result;
+}
+
+fn assert_round_trips(source_code: &str) {
+    let program = parse(source_code);
+    let printed = program.to_string();
+    let reparsed = parse(&printed);
+    assert_eq!(
+        without_spans(format!("{:?}", program)),
+        without_spans(format!("{:?}", reparsed)),
+        "printed form of {:?} was {:?}", source_code, printed,
+    );
+    assert_eq!(printed, reparsed.to_string());
+
+    let parenthesized = program.parenthesized().to_string();
+    let reparsed = parse(&parenthesized);
+    assert_eq!(
+        without_spans(format!("{:?}", program)),
+        without_spans(format!("{:?}", reparsed)),
+        "parenthesized form of {:?} was {:?}", source_code, parenthesized,
+    );
+}
+
+#[test]
+fn test_print_statements() {
+    let test_cases = [
+        ("let x = 5", "let x = 5;"),
+        ("return   x", "return x;"),
+        ("x + 1", "x + 1;"),
+        ("let s = \"a\\\"b\\\\c\\n\\t\\u{1b}\";", "let s = \"a\\\"b\\\\c\\n\\t\\u{1b}\";"),
+        ("[1, true, \"two\"]", "[1, true, \"two\"];"),
+        ("{\"a\": 1, 2: [3]}", "{\"a\": 1, 2: [3]};"),
+        ("add(1, 2)[0]", "add(1, 2)[0];"),
+        ("let x = 1; x", "let x = 1;\nx;"),
+    ];
+
+    for (source_code, expected) in test_cases {
+        assert_eq!(expected, parse(source_code).to_string());
+    }
+}
+
+#[test]
+fn test_print_adds_only_needed_parentheses() {
+    let test_cases = [
+        ("((1 + 2)) * 3", "(1 + 2) * 3;"),
+        ("1 + (2 * 3)", "1 + 2 * 3;"),
+        ("(1 + 2) + 3", "1 + 2 + 3;"),
+        ("1 - (2 - 3)", "1 - (2 - 3);"),
+        ("-(a + b)", "-(a + b);"),
+        ("-(-a)", "--a;"),
+        ("-a[0]", "-a[0];"),
+        ("(-a)[0]", "(-a)[0];"),
+        ("(a + b)(c)", "(a + b)(c);"),
+        ("!(a == b) != c", "!(a == b) != c;"),
+    ];
+
+    for (source_code, expected) in test_cases {
+        assert_eq!(expected, parse(source_code).to_string());
+    }
+}
+
+#[test]
+fn test_print_indents_blocks() {
+    let program = parse("let max = fn(a, b) { if (a > b) { return a; } else { b } }; fn() {}");
+    assert_eq!("\
+let max = fn(a, b) {
+    if (a > b) {
+        return a;
+    } else {
+        b;
+    };
+};
+fn() {};", program.to_string());
+}
+
+#[test]
+fn test_round_trip() {
+    let sources = [
+        "let five = 5; let ten = 10; let add = fn(x, y) { x + y; }; let result = add(five, ten);",
+        "!-5; 5 < 10 > 5; if (5 < 10) { return true; } else { return false; } 10 == 10; 10 != 9;",
+        "a + b * c + d / e - f; -a * b; 1 - (2 - 3); (a + b) * (c - d); 5 > 4 == 3 < 4",
+        "\"foo bar\"; \"tab\\there\"; \"quote \\\" and \\\\ backslash\"; \"emoji 🐒\"",
+        "[1, 2 * 2, 3 + 3][1 + 1]; f(x)[0]; (-a)[0]; a[0](1); fn(x) { x }(5)",
+        "{\"one\": 1, true: 2 * 3, 4: fn(x) { x }}; let h = {}; h[\"k\"]",
+        "let fib = fn(n) { if (n < 2) { n } else { fib(n - 1) + fib(n - 2) } }; fib(10)",
+        "if (x) { y } else { {1: 2} }; if (if (a) { b }) { c }",
+        "let ünïcödé = 1; ünïcödé + -(-1)",
+    ];
+
+    for source_code in sources {
+        assert_round_trips(source_code);
+    }
+}
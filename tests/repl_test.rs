@@ -0,0 +1,101 @@
+use tarzan::repl;
+
+fn run_repl(input: &str) -> String {
+    let mut output = Vec::new();
+    repl::start_with(input.as_bytes(), &mut output).unwrap();
+    return String::from_utf8(output).unwrap();
+}
+
+#[test]
+fn test_repl_evaluates_each_line() {
+    let output = run_repl("1 + 2\n\"a\" + \"b\"\n");
+    assert_eq!(">> 3\n>> ab\n>> \n", output);
+}
+
+#[test]
+fn test_repl_writes_puts_to_output() {
+    let output = run_repl("puts(\"hello\")\n");
+    assert_eq!(">> hello\nnull\n>> \n", output);
+}
+
+#[test]
+fn test_repl_keeps_bindings_across_lines() {
+    let output = run_repl("let add = fn(a, b) { a + b };\nlet x = 40;\nadd(x, 2)\n");
+    assert_eq!(">> >> >> 42\n>> \n", output);
+}
+
+#[test]
+fn test_repl_reports_parser_errors_and_continues() {
+    let output = run_repl("let x 5;\n5\n");
+    assert_eq!(">> error[E0101]: expected `=`, found `5`\n --> <repl>:1:7\n  |\n1 | let x 5;\n  |       ^\n>> 5\n>> \n", output);
+}
+
+#[test]
+fn test_repl_reports_runtime_errors() {
+    let output = run_repl("foo\n");
+    assert_eq!(">> error[E0201]: identifier not found: foo\n --> <repl>:1:1\n  |\n1 | foo\n  | ^^^\n>> \n", output);
+}
+
+#[test]
+fn test_repl_exits_on_end_of_input() {
+    assert_eq!(">> \n", run_repl(""));
+}
+
+#[test]
+fn test_repl_continues_unfinished_input() {
+    let output = run_repl("let add = fn(a, b) {\n  a +\n  b\n};\nadd(1,\n 2)\n");
+    assert_eq!(">> .. .. .. >> .. 3\n>> \n", output);
+}
+
+#[test]
+fn test_repl_continues_unterminated_string() {
+    let output = run_repl("\"multi\nline\"\n");
+    assert_eq!(">> .. multi\nline\n>> \n", output);
+}
+
+#[test]
+fn test_repl_empty_line_submits_unfinished_input() {
+    let output = run_repl("(1 + 2\n\n");
+    assert_eq!(">> .. error[E0104]: unclosed delimiter `(`\n --> <repl>:1:1\n  |\n1 | (1 + 2\n  | ^\n  = help: expected `)` to close `(` opened here\n>> \n", output);
+}
+
+#[test]
+fn test_repl_tokens_command() {
+    let output = run_repl(":tokens let x = 5;\n");
+    assert_eq!(">> 1:1 Let\n\
+        1:5 Identifier { literal: \"x\" }\n\
+        1:7 Assign\n\
+        1:9 Int { literal: \"5\" }\n\
+        1:10 Semicolon\n\
+        1:11 Eof\n\
+        >> \n", output);
+}
+
+#[test]
+fn test_repl_ast_command() {
+    let output = run_repl(":ast x\n");
+    assert!(output.starts_with(">> Expression(\n    Identifier {\n"), "got: {}", output);
+}
+
+#[test]
+fn test_repl_env_and_reset_commands() {
+    let output = run_repl("let b = 2;\nlet a = \"one\";\n:env\n:reset\n:env\na\n");
+    assert_eq!(">> >> >> a = one\nb = 2\n>> >> >> error[E0201]: identifier not found: a\n --> <repl>:1:1\n  |\n1 | a\n  | ^\n>> \n", output);
+}
+
+#[test]
+fn test_repl_load_command() {
+    let path = std::env::temp_dir().join(format!("tarzan_repl_load_{}.mk", std::process::id()));
+    std::fs::write(&path, "let double = fn(x) {\n  x * 2\n};\n").unwrap();
+
+    let output = run_repl(&format!(":load {}\ndouble(21)\n", path.display()));
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(">> >> 42\n>> \n", output);
+}
+
+#[test]
+fn test_repl_quit_and_unknown_commands() {
+    let output = run_repl(":nope\n:quit\n1\n");
+    assert_eq!(">> unknown command: :nope (try :help)\n>> ", output);
+}
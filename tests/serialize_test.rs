@@ -0,0 +1,78 @@
+use tarzan::{lexer, parser};
+use tarzan::ast::Program;
+use tarzan::serialize::{json, sexpr};
+use tarzan::token::{SpannedToken, Token};
+
+fn parse(source_code: &str) -> Program {
+    let mut parser = parser::new(lexer::new(source_code.into()));
+    return parser.parse().unwrap_or_else(|errors| {
+        let messages: Vec<String> = errors.iter().map(|error| error.to_string()).collect();
+        panic!("parser errors for {:?}: {:?}", source_code, messages)
+    });
+}
+
+fn lex(source_code: &str) -> Vec<SpannedToken> {
+    let mut lexer = lexer::new(source_code.into());
+    let mut tokens = Vec::new();
+    loop {
+        let spanned_token = lexer.next_token().unwrap();
+        let at_end = spanned_token.token == Token::Eof;
+        tokens.push(spanned_token);
+        if at_end {
+            return tokens;
+        }
+    }
+}
+
+#[test]
+fn test_tokens_to_json() {
+    let expected = concat!(
+        r#"{"version":1,"tokens":["#,
+        r#"{"kind":"Str","literal":"a\"\n","span":{"start":0,"end":7,"line":1,"column":1}},"#,
+        r#"{"kind":"Plus","span":{"start":8,"end":9,"line":1,"column":9}},"#,
+        r#"{"kind":"Identifier","literal":"é","span":{"start":10,"end":12,"line":1,"column":11}},"#,
+        r#"{"kind":"Eof","span":{"start":12,"end":12,"line":1,"column":12}}"#,
+        "]}",
+    );
+    assert_eq!(json::tokens(&lex(r#""a\"\n" + é"#)), expected);
+}
+
+#[test]
+fn test_program_to_json() {
+    let expected = concat!(
+        r#"{"version":1,"kind":"Program","statements":[{"kind":"Let","name":"f","value":"#,
+        r#"{"kind":"Function","parameters":[{"kind":"Identifier","name":"a","span":{"start":11,"end":12,"line":1,"column":12}}],"#,
+        r#""body":{"kind":"Block","statements":[{"kind":"Return","value":"#,
+        r#"{"kind":"Prefix","operator":"-","right":{"kind":"Integer","value":1,"span":{"start":24,"end":25,"line":1,"column":25}},"#,
+        r#""span":{"start":23,"end":25,"line":1,"column":24}},"span":{"start":16,"end":26,"line":1,"column":17}}],"#,
+        r#""span":{"start":14,"end":28,"line":1,"column":15}},"span":{"start":8,"end":28,"line":1,"column":9}},"#,
+        r#""span":{"start":0,"end":29,"line":1,"column":1}}]}"#,
+    );
+    assert_eq!(json::program(&parse("let f = fn(a) { return -1; };")), expected);
+}
+
+#[test]
+fn test_tokens_to_sexpr() {
+    assert_eq!(sexpr::tokens(&lex("fn(x) { \"hi\" }")), concat!(
+        r#"(tokens (Function) (LeftParenthesis) (Identifier "x") (RightParenthesis)"#,
+        r#" (LeftBracket) (Str "hi") (RightBracket) (Eof))"#,
+    ));
+}
+
+#[test]
+fn test_program_to_sexpr() {
+    let tests = vec![
+        ("let x = add(1, 2 * y);", "(program (let x (call add 1 (* 2 y))))"),
+        ("return !true;", "(program (return (! true)))"),
+        ("if (a < b) { a } else { b; c }", "(program (if (< a b) (block a) (block b c)))"),
+        ("if (a) { }", "(program (if a (block) nil))"),
+        ("fn(a, b) { a }", "(program (fn (a b) (block a)))"),
+        ("[1, \"two\\n\"][0]", "(program (index (array 1 \"two\\n\") 0))"),
+        ("{\"a\": 1, true: x}", "(program (hash (\"a\" 1) (true x)))"),
+        ("", "(program)"),
+    ];
+
+    for (input, expected) in tests {
+        assert_eq!(sexpr::program(&parse(input)), expected, "for {:?}", input);
+    }
+}
@@ -0,0 +1,91 @@
+use tarzan::{lexer, parser};
+use tarzan::ast::{Expression, Program};
+use tarzan::ast::fold::{self, Folder};
+use tarzan::ast::visit::{self, Visitor};
+use tarzan::ast::visit_mut::{self, VisitorMut};
+use tarzan::token::Token;
+
+fn parse(source_code: &str) -> Program {
+    let mut parser = parser::new(lexer::new(source_code.into()));
+    return parser.parse().unwrap_or_else(|errors| {
+        let messages: Vec<String> = errors.iter().map(|error| error.to_string()).collect();
+        panic!("parser errors for {:?}: {:?}", source_code, messages)
+    });
+}
+
+struct IdentifierCollector {
+    names: Vec<String>,
+}
+
+impl Visitor for IdentifierCollector {
+    fn visit_expression(&mut self, expression: &Expression) {
+        if let Expression::Identifier { token: Token::Identifier { literal: name }, .. } = expression {
+            self.names.push(name.clone());
+        }
+        visit::walk_expression(self, expression);
+    }
+}
+
+struct Renamer<'a> {
+    from: &'a str,
+    to: &'a str,
+}
+
+impl VisitorMut for Renamer<'_> {
+    fn visit_expression(&mut self, expression: &mut Expression) {
+        if let Expression::Identifier { token: Token::Identifier { literal: name }, .. } = expression {
+            if name == self.from {
+                *name = self.to.to_string();
+            }
+        }
+        visit_mut::walk_expression_mut(self, expression);
+    }
+}
+
+struct AdditionFolder;
+
+impl Folder for AdditionFolder {
+    fn fold_expression(&mut self, expression: Expression) -> Expression {
+        match fold::fold_expression(self, expression) {
+            Expression::InfixExpression { operator, left, right, span } if operator == "+" => match (*left, *right) {
+                (Expression::IntegerLiteral { value: left, .. }, Expression::IntegerLiteral { value: right, .. }) => {
+                    Expression::IntegerLiteral { token: Token::Int { literal: (left + right).to_string() }, value: left + right, span }
+                }
+                (left, right) => Expression::InfixExpression { operator, left: Box::new(left), right: Box::new(right), span },
+            },
+            expression => expression,
+        }
+    }
+}
+
+#[test]
+fn test_visitor_reaches_every_expression() {
+    let program = parse("
+        let add = fn(a, b) { a + b };
+        if (x < y) { add(x, [y][0]) } else { {z: w} };
+        return !v;
+    ");
+
+    let mut collector = IdentifierCollector { names: Vec::new() };
+    collector.visit_program(&program);
+
+    assert_eq!(collector.names, vec!["a", "b", "a", "b", "x", "y", "add", "x", "y", "z", "w", "v"]);
+}
+
+#[test]
+fn test_visitor_mut_changes_nodes_in_place() {
+    let mut program = parse("let y = x * 2; fn(x) { x }(x);");
+
+    Renamer { from: "x", to: "input" }.visit_program(&mut program);
+
+    assert_eq!(program.to_string(), "let y = input * 2;\nfn(input) {\n    input;\n}(input);");
+}
+
+#[test]
+fn test_folder_rebuilds_nodes() {
+    let program = parse("let a = 1 + 2 + 3; puts(fn() { return 4 + 5; }, x + 1);");
+
+    let folded = AdditionFolder.fold_program(program);
+
+    assert_eq!(folded.to_string(), "let a = 6;\nputs(fn() {\n    return 9;\n}, x + 1);");
+}
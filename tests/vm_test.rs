@@ -0,0 +1,80 @@
+use std::rc::Rc;
+use tarzan::{compiler, lexer, parser, vm};
+use tarzan::evaluator::error::RuntimeError;
+use tarzan::evaluator::object::{Capture, Object};
+use tarzan::token::Span;
+
+fn run(source_code: &str) -> Result<Object, RuntimeError> {
+    let lexer = lexer::new(source_code.into());
+    let mut parser = parser::new(lexer);
+    let program = parser.parse().unwrap_or_else(|errors| panic!("found parser errors: {:?}", errors));
+
+    let bytecode = compiler::compile(&program).unwrap_or_else(|error| panic!("found compile error: {}", error));
+    vm::run(&bytecode)
+}
+
+struct VmTestCase {
+    source_code: &'static str,
+    expected: Object,
+}
+
+fn assert_vm_test_cases(test_cases: Vec<VmTestCase>) {
+    for test_case in test_cases {
+        let result = run(test_case.source_code).unwrap_or_else(|error| panic!("found runtime error: {}", error));
+        assert_eq!(test_case.expected, result, "source code: {}", test_case.source_code);
+    }
+}
+
+#[test]
+fn test_run_closures() {
+    assert_vm_test_cases(vec![
+        VmTestCase { source_code: "let adder = fn(x) { fn(y) { x + y } }; adder(2)(3);", expected: Object::Integer(5) },
+        VmTestCase {
+            source_code: "let add_two = fn(x) { fn(y) { x + y } }(2); let add_ten = fn(x) { fn(y) { x + y } }(10); add_two(1) + add_ten(1);",
+            expected: Object::Integer(14),
+        },
+        VmTestCase {
+            source_code: "let f = fn(a) { fn(b) { fn(c) { a + b + c } } }; f(1)(2)(3);",
+            expected: Object::Integer(6),
+        },
+        VmTestCase {
+            source_code: "let f = fn(a) { let g = fn() { a * 2 }; let h = fn() { g() + 1 }; h }; f(20)();",
+            expected: Object::Integer(41),
+        },
+        VmTestCase {
+            source_code: "let count = fn(n) { let down = fn(x) { if (x == 0) { n } else { down(x - 1) } }; down(n) }; count(5);",
+            expected: Object::Integer(5),
+        },
+    ]);
+}
+
+#[test]
+fn test_run_recursive_closures_are_freed() {
+    let closure = match run("let f = fn() { let g = fn(n) { if (n == 0) { g } else { h(n - 1) } }; let h = fn(n) { g(n) }; g }; f()(3);") {
+        Ok(Object::Closure(closure)) => closure,
+        result => panic!("result is not a closure, got: {:?}", result),
+    };
+    let locals = match &closure.free[..] {
+        [Capture::Shared(locals, _), ..] => Rc::downgrade(locals),
+        free => panic!("closure does not share the locals of f, got {} captures", free.len()),
+    };
+    drop(closure);
+    assert!(locals.upgrade().is_none(), "the locals of f outlive every closure that uses them");
+}
+
+#[test]
+fn test_run_error_spans() {
+    let error = run("let x = 1;\nlet f = fn(a) {\n  a + true\n};\nf(x);").unwrap_err();
+    assert_eq!("type mismatch: INTEGER + BOOLEAN", error.message);
+    assert_eq!(Some(3), error.span.map(|span: Span| span.line));
+
+    let error = run("let f = fn() { 1 };\nf(1, 2);").unwrap_err();
+    assert_eq!("wrong number of arguments: expected 0, got 2", error.message);
+    assert_eq!(Some(2), error.span.map(|span: Span| span.line));
+}
+
+#[test]
+fn test_run_stack_overflow() {
+    let error = run("let f = fn(n) { f(n + 1) }; f(0);").unwrap_err();
+    assert_eq!(format!("stack overflow: more than {} nested calls", vm::MAX_FRAMES), error.message);
+}